@@ -0,0 +1,108 @@
+use std::{fs, io, path};
+
+use image::{DynamicImage, RgbaImage, imageops::FilterType::CatmullRom};
+use resvg::{tiny_skia, usvg};
+
+use crate::{error::Result, pe, utils};
+
+pub const POSTER_SIZE: u32 = 256;
+pub const POSTER_FORMATS: [&str; 9] = [
+    "bmp", "png", "jpg", "jpeg", "webp", "ico", "gif", "svg", "svgz",
+];
+
+// SVGs are drawn so that their longest side is this long, leaving room to crop into
+const SVG_SIZE: f32 = 1024.0;
+
+/// Part of an image kept as a poster: a square around the focal point `x`, `y` (fractions of
+/// the width and height), whose side is the shortest side of the image divided by `zoom`.
+#[derive(Debug, Clone, Copy)]
+pub struct Crop {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+}
+
+impl Default for Crop {
+    fn default() -> Self {
+        Crop {
+            x: 0.5,
+            y: 0.5,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Crop {
+    /// The square that is kept of a `width` by `height` image, as `(x, y, side)`.
+    /// The focal point is moved towards the middle where the square would leave the image.
+    pub fn bounds(&self, width: u32, height: u32) -> (u32, u32, u32) {
+        let side = (width.min(height) as f32 / self.zoom.max(1.0))
+            .round()
+            .max(1.0);
+        let start = |focus: f32, length: u32| {
+            (focus.clamp(0.0, 1.0) * length as f32 - side / 2.0)
+                .clamp(0.0, length as f32 - side)
+                .round() as u32
+        };
+        (start(self.x, width), start(self.y, height), side as u32)
+    }
+}
+
+fn open_svg(file: &path::Path) -> Result<DynamicImage> {
+    let mut options = usvg::Options {
+        resources_dir: file.parent().map(|p| p.to_path_buf()),
+        ..Default::default()
+    };
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_data(&fs::read(file)?, &options)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let size = tree.size();
+    let scale = SVG_SIZE / size.width().max(size.height());
+    let (width, height) = (
+        (size.width() * scale).ceil() as u32,
+        (size.height() * scale).ceil() as u32,
+    );
+    let Some(mut pixmap) = tiny_skia::Pixmap::new(width, height) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "SVG has no size.").into());
+    };
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    // tiny-skia premultiplies alpha, images do not
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let color = p.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    Ok(DynamicImage::ImageRgba8(
+        RgbaImage::from_raw(width, height, pixels).unwrap(), // safe unwrap
+    ))
+}
+
+/// Opens any image that can become a poster. Icons decode to their largest frame
+/// and animated GIFs to their first one.
+pub fn open(file: &path::Path) -> Result<DynamicImage> {
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "svg" | "svgz" => open_svg(file),
+        "ico" => pe::extract_ico(&fs::read(file)?),
+        _ => utils::open_image(file),
+    }
+}
+
+/// Crops `image` to a square poster of `POSTER_SIZE` pixels.
+pub fn crop(image: &DynamicImage, crop: &Crop) -> DynamicImage {
+    let (x, y, side) = crop.bounds(image.width(), image.height());
+    image
+        .crop_imm(x, y, side, side)
+        .resize_exact(POSTER_SIZE, POSTER_SIZE, CatmullRom)
+}
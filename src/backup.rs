@@ -0,0 +1,120 @@
+use std::{
+    cmp::Reverse,
+    fs, io, path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::Result;
+
+pub const DEFAULT_RETENTION: usize = 10;
+
+#[derive(Debug)]
+pub struct Backup {
+    pub path: path::PathBuf,
+    pub created: u64,
+}
+
+impl Clone for Backup {
+    fn clone(&self) -> Self {
+        Backup {
+            path: self.path.clone(),
+            created: self.created,
+        }
+    }
+}
+
+impl Backup {
+    pub fn name(&self) -> String {
+        format_timestamp(self.created / 1000)
+    }
+
+    pub fn file(&self, name: &str) -> Option<path::PathBuf> {
+        let file = self.path.join(name);
+        file.exists().then_some(file)
+    }
+}
+
+/// Creates a new timestamped directory under `root`, filled in by `write`.
+pub fn create(root: &path::Path, write: impl FnOnce(&path::Path) -> Result<()>) -> Result<Backup> {
+    let mut created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(io::Error::other)?
+        .as_millis() as u64;
+    fs::create_dir_all(root)?;
+    // Saves may follow each other within a millisecond, every backup gets a directory of its own
+    let target = loop {
+        let target = root.join(created.to_string());
+        match fs::create_dir(&target) {
+            Ok(()) => break target,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => created += 1,
+            Err(e) => return Err(e.into()),
+        }
+    };
+    if let Err(e) = write(&target) {
+        // A partial backup must not be offered for restoring
+        let _ = fs::remove_dir_all(&target);
+        return Err(e);
+    }
+    Ok(Backup {
+        path: target,
+        created,
+    })
+}
+
+/// Removes the oldest backups under `root` so that at most `retention` remain.
+pub fn prune(root: &path::Path, retention: usize) -> io::Result<()> {
+    for old in list(root)?.iter().skip(retention.max(1)) {
+        fs::remove_dir_all(&old.path)?;
+    }
+    Ok(())
+}
+
+/// Returns every backup under `root`, newest first.
+pub fn list(root: &path::Path) -> io::Result<Vec<Backup>> {
+    if !root.exists() {
+        return Ok(vec![]);
+    }
+    let mut backups: Vec<Backup> = vec![];
+    for entry in root.read_dir()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(created) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u64>().ok())
+        {
+            backups.push(Backup {
+                path: entry.path(),
+                created,
+            });
+        }
+    }
+    backups.sort_by_key(|b| Reverse(b.created));
+    Ok(backups)
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS` (UTC).
+fn format_timestamp(seconds: u64) -> String {
+    let (days, rem) = (seconds / 86400, seconds % 86400);
+    // Civil-from-days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
@@ -0,0 +1,210 @@
+use std::{fs, io};
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{depots::Depot, error::Result, types::logitech::Application};
+
+// Database of games LGHUB detects, next to the images of its posters. Its entries look like
+// the applications of LGHUB's settings, see tests/fixtures/depots
+const CATALOG_FILE: [&str; 2] = ["core_apps", "applications.json"];
+// Fields of a detection rule that say the most about it, in order of preference
+const DETECTION_FIELDS: [&str; 6] = [
+    "appId",
+    "executable",
+    "registryKey",
+    "registryPath",
+    "path",
+    "glob",
+];
+
+/// One way LGHUB recognises that a game is installed, e.g. a Steam app id.
+#[derive(Debug)]
+pub struct Detection {
+    pub kind: String,
+    pub value: String,
+}
+
+impl Clone for Detection {
+    fn clone(&self) -> Self {
+        Detection {
+            kind: self.kind.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl Detection {
+    // Every rule is an object with a single key naming its kind, e.g. {"steam": {"appId": "..."}}
+    fn parse(rule: &Value) -> Option<Self> {
+        let (kind, value) = rule.as_object()?.iter().next()?;
+        let value = match value {
+            Value::String(value) => value.clone(),
+            Value::Object(fields) => DETECTION_FIELDS
+                .iter()
+                .find_map(|f| fields.get(*f).and_then(|v| v.as_str()))
+                .map(String::from)
+                .unwrap_or(value.to_string()),
+            _ => value.to_string(),
+        };
+        Some(Detection {
+            kind: kind.clone(),
+            value,
+        })
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}: {}", self.kind, self.value)
+    }
+}
+
+/// A game of LGHUB's bundled database.
+#[derive(Debug)]
+pub struct CatalogGame {
+    pub database_id: String,
+    pub name: String,
+    pub version: Option<usize>,
+    pub poster_url: Option<String>,
+    pub poster_title_position: Option<String>,
+    pub detection: Vec<Detection>,
+}
+
+impl Clone for CatalogGame {
+    fn clone(&self) -> Self {
+        CatalogGame {
+            database_id: self.database_id.clone(),
+            name: self.name.clone(),
+            version: self.version,
+            poster_url: self.poster_url.clone(),
+            poster_title_position: self.poster_title_position.clone(),
+            detection: self.detection.clone(),
+        }
+    }
+}
+
+impl CatalogGame {
+    fn parse(entry: &Value) -> Option<Self> {
+        let text = |key: &str| entry.get(key).and_then(|v| v.as_str()).map(String::from);
+        // Applications of LGHUB's settings refer to the entry by its id, as their databaseId
+        Some(CatalogGame {
+            database_id: text("applicationId")?,
+            name: text("name")?,
+            version: entry
+                .get("version")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize),
+            poster_url: text("posterUrl"),
+            poster_title_position: text("posterTitlePosition"),
+            detection: entry
+                .get("detection")
+                .and_then(|d| d.as_array())
+                .map(|rules| rules.iter().filter_map(Detection::parse).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// An application for this game like the ones LGHUB adds once it detects it.
+    pub fn as_application(&self) -> Application {
+        Application {
+            name: self.name.clone(),
+            applicationId: Uuid::new_v4().to_string(),
+            applicationPath: None,
+            databaseId: Some(self.database_id.clone()),
+            version: self.version,
+            isCustom: None,
+            posterPath: None,
+            applicationFolder: None,
+            isInstalled: Some(true),
+            posterTitlePosition: self.poster_title_position.clone(),
+            posterUrl: self.poster_url.clone(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// Every game of the database shipped with `depot`, sorted by name.
+pub fn read(depot: &Depot) -> Result<Vec<CatalogGame>> {
+    let Some(file) = depot.find(&CATALOG_FILE) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "LGHUB's game database was not found in its depots.",
+        )
+        .into());
+    };
+    let document: Value = serde_json::from_slice(&fs::read(&file)?)?;
+    let Some(entries) = document.get("applications").and_then(|a| a.as_array()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a list of applications.", file.display()),
+        )
+        .into());
+    };
+
+    let mut games: Vec<CatalogGame> = entries.iter().filter_map(CatalogGame::parse).collect();
+    games.sort_by_key(|g| g.name.to_lowercase());
+    Ok(games)
+}
+
+/// Games whose name contains every word of `query`, ignoring case.
+pub fn search<'a>(games: &'a [CatalogGame], query: &str) -> Vec<&'a CatalogGame> {
+    let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+    games
+        .iter()
+        .filter(|g| {
+            let name = g.name.to_lowercase();
+            words.iter().all(|w| name.contains(w))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path;
+
+    use super::{read, search};
+    use crate::depots::Depot;
+
+    fn depot() -> Depot {
+        let root = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/depots");
+        Depot::open(&root).unwrap()
+    }
+
+    #[test]
+    fn reads_newest_depot() {
+        let depot = depot();
+        let games = read(&depot).unwrap();
+        // Sorted by name, entries without one are skipped
+        let names: Vec<&str> = games.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["arena shooter", "Racing Game"]);
+
+        let racing = &games[1];
+        assert_eq!(racing.database_id, "b3a1c7e2-racing");
+        assert_eq!(racing.version, Some(3));
+        let detection: Vec<String> = racing.detection.iter().map(|d| d.label()).collect();
+        assert_eq!(
+            detection,
+            [
+                "steam: 244210",
+                "winRegistry: racing.exe",
+                "osxBundle: {\"bundleId\":\"com.example.racing\"}",
+            ]
+        );
+        assert!(depot.image("RACING_POSTER.png").is_some());
+        assert_eq!(search(&games, "GAME rac").len(), 1);
+    }
+
+    #[test]
+    fn adds_games_as_installed_applications() {
+        let games = read(&depot()).unwrap();
+        let racing = games[1].as_application();
+        assert_eq!(racing.databaseId.as_deref(), Some("b3a1c7e2-racing"));
+        assert_eq!(racing.isInstalled, Some(true));
+        assert_eq!(racing.isCustom, None);
+        assert_eq!(
+            racing.posterUrl.as_deref(),
+            Some("https://assets.example.com/images/racing_poster.png")
+        );
+        // Without a poster there is nothing to fetch
+        assert_eq!(games[0].as_application().posterUrl, None);
+    }
+}
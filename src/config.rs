@@ -2,22 +2,53 @@ use std::{
     collections::HashMap,
     env,
     ffi::OsString,
-    fs, io, path,
+    fmt, fs, io, path,
     sync::{OnceLock, RwLock},
 };
 
+use serde_json::Value;
+use uuid::Uuid;
+
 use crate::{
+    backup::{self, Backup},
+    catalog::CatalogGame,
+    depots,
+    duplicates::{self, DuplicateGroup},
+    error::{GProfilesError, Result},
+    merge::{MergeConflicts, Resolution, merge, merge_profiles},
+    migrations,
+    relocate::{self, Field, Relocation},
+    store::{self, MemoryStore, SettingsStore},
     types::{
-        gprofiles::{GProfilesData, Keybind},
+        gprofiles::{GProfilesData, Keybind, Target},
         logitech::{Application, LogitechData, Profile},
     },
-    utils::{APPLICATION_NAME_DESKTOP, Serializable, get_row},
+    utils::{self, APPLICATION_NAME_DESKTOP, PROFILE_NAME_DEFAULT},
 };
 
 pub static CONFIG: OnceLock<RwLock<Config>> = OnceLock::new();
 
+pub fn init_config() -> Result<()> {
+    let (config, result) = match Config::new() {
+        Ok(config) => (config, Ok(())),
+        Err(e) => {
+            let gprofiles_settings =
+                get_default_storage("GProfiles", Some("settings.json {}")).ok();
+            (Config::fallback(gprofiles_settings), Err(e))
+        }
+    };
+    let _ = CONFIG.set(RwLock::new(config));
+    result
+}
+
 pub fn get_config() -> &'static RwLock<Config> {
-    CONFIG.get_or_init(|| RwLock::new(Config::new()))
+    CONFIG.get_or_init(|| RwLock::new(Config::default()))
+}
+
+pub fn reload_config() -> Result<()> {
+    let config = Config::new()?;
+    *get_config().write().unwrap() = config;
+    Ok(())
 }
 
 fn get_default_storage(identifier: &str, data: Option<&str>) -> io::Result<path::PathBuf> {
@@ -61,62 +92,763 @@ fn get_default_storage(identifier: &str, data: Option<&str>) -> io::Result<path:
     Ok(target)
 }
 
+#[derive(Debug)]
+pub struct Deletion {
+    pub application: Application,
+    pub profiles: Vec<Profile>,
+    pub keybinds: Vec<Keybind>,
+    pub images: Vec<path::PathBuf>,
+}
+
+impl fmt::Display for Deletion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Application: {}", self.application.name)?;
+        writeln!(f, "Profiles ({}):", self.profiles.len())?;
+        for profile in &self.profiles {
+            writeln!(f, "- {}", profile.name)?;
+        }
+        writeln!(f, "Keybinds: {}", self.keybinds.len())?;
+        write!(f, "Images ({}):", self.images.len())?;
+        for image in &self.images {
+            write!(f, "\n- {}", image.display())?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct Config {
+    store: Box<dyn SettingsStore>,
     applications: Vec<Application>,
     profiles: Vec<Profile>,
     keybinds: HashMap<String, Vec<Keybind>>,
-    gprofiles_settings: path::PathBuf,
+    gprofiles_settings: Option<path::PathBuf>,
     lghub_location: path::PathBuf,
+    backups: Option<path::PathBuf>,
+    backup_retention: Option<usize>,
+    lghub: Option<String>,
+    targets: Vec<Target>,
+    library_roots: HashMap<String, Vec<String>>,
+    poster_base: Option<String>,
+    depots: Option<String>,
+
+    // State of the store when it was last read or written, used to detect LGHUB's own writes
+    fingerprint: u64,
+    gprofiles_fingerprint: u64,
+    base_applications: Vec<Application>,
+    base_profiles: Vec<Profile>,
+    // Stands in for settings that could not be loaded, so nothing may be saved over them
+    fallback: bool,
 }
 
 impl Config {
-    pub fn new() -> Self {
-        let gprofiles_settings =
-            get_default_storage("GProfiles", Some("settings.json {}")).unwrap();
-        let gprofiles_data: GProfilesData = gprofiles_settings.to_json().unwrap();
+    pub fn new() -> Result<Self> {
+        let gprofiles_settings = get_default_storage("GProfiles", Some("settings.json {}"))?;
+        let gprofiles_data = migrations::load(&gprofiles_settings)?;
 
         let lghub_location = if gprofiles_data.lghub.is_none() {
-            get_default_storage("LGHUB", None).unwrap()
+            get_default_storage("LGHUB", None)?
         } else {
-            path::PathBuf::from(&gprofiles_data.lghub.unwrap()) // safe unwrap
+            path::PathBuf::from(gprofiles_data.lghub.as_ref().unwrap()) // safe unwrap
+        };
+        let store = store::open(&lghub_location);
+        Self::from_parts(
+            store,
+            Some((gprofiles_settings, gprofiles_data)),
+            lghub_location,
+        )
+    }
+
+    pub fn with_store(
+        store: Box<dyn SettingsStore>,
+        gprofiles_settings: Option<path::PathBuf>,
+        lghub_location: path::PathBuf,
+    ) -> Result<Self> {
+        let gprofiles = match gprofiles_settings {
+            Some(file) => {
+                let data = migrations::load(&file)?;
+                Some((file, data))
+            }
+            None => None,
+        };
+        Self::from_parts(store, gprofiles, lghub_location)
+    }
+
+    /// Empty LGHUB settings, used when the real ones could not be loaded. They cannot be
+    /// saved, but GProfiles' own settings at `gprofiles_settings` still can, so that another
+    /// LGHUB location can be picked.
+    pub fn fallback(gprofiles_settings: Option<path::PathBuf>) -> Self {
+        let store = || Box::new(MemoryStore::default());
+        // Settings that cannot be read are left alone rather than overwritten
+        let mut config = gprofiles_settings
+            .and_then(|file| Self::with_store(store(), Some(file), path::PathBuf::new()).ok())
+            .unwrap_or_else(|| Self::with_store(store(), None, path::PathBuf::new()).unwrap()); // safe unwrap
+        config.fallback = true;
+        config
+    }
+
+    fn from_parts(
+        store: Box<dyn SettingsStore>,
+        gprofiles: Option<(path::PathBuf, GProfilesData)>,
+        lghub_location: path::PathBuf,
+    ) -> Result<Self> {
+        let (gprofiles_settings, gprofiles_data, gprofiles_fingerprint) = match gprofiles {
+            Some((file, data)) => {
+                let fingerprint = utils::fingerprint(&fs::read(&file)?);
+                (Some(file), data, fingerprint)
+            }
+            None => (None, GProfilesData::default(), 0),
         };
-        let lghub_settings = lghub_location.join("settings.db");
 
-        let (applications, profiles) = if lghub_settings.exists() {
-            let data: Vec<u8> = get_row(&lghub_settings, "data", "file").unwrap();
-            let decoded = String::from_utf8(data).unwrap();
-            let logitech_data: LogitechData = decoded.to_json().unwrap();
+        let (applications, profiles, fingerprint) = if store.exists() {
+            let data = store.read()?;
+            let fingerprint = utils::fingerprint(&data);
+            let logitech_data: LogitechData = serde_json::from_slice(&data)?;
             (
                 logitech_data.applications.applications,
                 logitech_data.profiles.profiles,
+                fingerprint,
             )
         } else {
             // maybe show a warning?
-            (vec![], vec![])
+            (vec![], vec![], 0)
         };
         let keybinds = gprofiles_data.keybinds.unwrap_or_default();
+        let backups = gprofiles_settings
+            .as_ref()
+            .map(|f| f.with_file_name("backups"));
 
-        Self {
-            applications,
-            profiles,
+        Ok(Self {
+            store,
             gprofiles_settings,
             lghub_location,
             keybinds,
+            backups,
+            backup_retention: gprofiles_data.backups,
+            lghub: gprofiles_data.lghub,
+            targets: gprofiles_data.targets.unwrap_or_default(),
+            library_roots: gprofiles_data.libraries.unwrap_or_default(),
+            poster_base: gprofiles_data.posters,
+            depots: gprofiles_data.depots,
+            fingerprint,
+            gprofiles_fingerprint,
+            base_applications: applications.clone(),
+            base_profiles: profiles.clone(),
+            fallback: false,
+            applications,
+            profiles,
+        })
+    }
+
+    pub fn save(&mut self, resolution: Resolution) -> Result<()> {
+        if self.fallback {
+            return Err(io::Error::other(
+                "Settings could not be loaded, so changes cannot be saved.",
+            )
+            .into());
+        }
+        self.backup()?;
+        let mut written: Option<u64> = None;
+        let result = self.store.update(&mut |data: Vec<u8>| {
+            let mut document: LogitechData = serde_json::from_slice(&data)?;
+
+            if utils::fingerprint(&data) != self.fingerprint {
+                // LGHUB wrote to its settings since we last read them
+                let (applications, mut application_conflicts) = merge(
+                    &self.base_applications,
+                    &self.applications,
+                    &document.applications.applications,
+                    resolution,
+                );
+                let (profiles, profile_conflicts) = merge_profiles(
+                    &self.base_profiles,
+                    &self.profiles,
+                    &document.profiles.profiles,
+                    resolution,
+                );
+                application_conflicts.extend(profile_conflicts);
+                if !application_conflicts.is_empty() {
+                    return Err(GProfilesError::Conflict(MergeConflicts(
+                        application_conflicts,
+                    )));
+                }
+                document.applications.applications = applications;
+                document.profiles.profiles = profiles;
+            } else {
+                document.applications.applications = self.applications.clone();
+                document.profiles.profiles = self.profiles.clone();
+            }
+
+            let value: Value = document.into();
+            let new = serde_json::to_vec(&value)?;
+            written = Some(utils::fingerprint(&new));
+            Ok(new)
+        });
+        result?;
+
+        if let Some(fingerprint) = written {
+            // Re-read what was written so merged changes from LGHUB show up
+            let logitech_data = self.store.load()?;
+            self.applications = logitech_data.applications.applications;
+            self.profiles = logitech_data.profiles.profiles;
+            self.fingerprint = fingerprint;
+            self.base_applications = self.applications.clone();
+            self.base_profiles = self.profiles.clone();
+        }
+        Ok(())
+    }
+
+    pub fn save_gprofiles(&mut self) -> Result<()> {
+        let Some(gprofiles_settings) = &self.gprofiles_settings else {
+            if self.fallback {
+                return Err(io::Error::other(
+                    "GProfiles' settings could not be loaded, so changes cannot be saved.",
+                )
+                .into());
+            }
+            return Ok(());
+        };
+        let data = GProfilesData {
+            schema_version: migrations::SCHEMA_VERSION,
+            lghub: self.lghub.clone(),
+            keybinds: (!self.keybinds.is_empty()).then(|| self.keybinds.clone()),
+            backups: self.backup_retention,
+            targets: (!self.targets.is_empty()).then(|| self.targets.clone()),
+            libraries: (!self.library_roots.is_empty()).then(|| self.library_roots.clone()),
+            posters: self.poster_base.clone(),
+            depots: self.depots.clone(),
+        };
+        let serialized = serde_json::to_vec_pretty(&data)?;
+        utils::write_atomic(gprofiles_settings, &serialized)?;
+        self.gprofiles_fingerprint = utils::fingerprint(&serialized);
+        Ok(())
+    }
+
+    pub fn get_lghub_location(&self) -> &path::Path {
+        &self.lghub_location
+    }
+
+    pub fn get_targets(&self) -> &Vec<Target> {
+        &self.targets
+    }
+
+    pub fn add_target(&mut self, location: &path::Path) -> Result<Target> {
+        store::validate_location(location)?;
+        let path = location.to_string_lossy().to_string();
+        if let Some(existing) = self.targets.iter().find(|t| t.path == path) {
+            return Ok(existing.clone());
+        }
+        // Remember where we came from, so it is possible to switch back
+        let current = self.lghub_location.to_string_lossy().to_string();
+        if self.targets.is_empty() && current != path {
+            self.targets.push(Target {
+                name: String::from("Default"),
+                path: current,
+            });
+        }
+
+        let base = location
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or(path.clone());
+        let mut name = base.clone();
+        let mut count = 1;
+        while self.targets.iter().any(|t| t.name == name) {
+            count += 1;
+            name = format!("{} ({})", base, count);
+        }
+        let target = Target { name, path };
+        self.targets.push(target.clone());
+        Ok(target)
+    }
+
+    pub fn remove_target(&mut self, name: &String) {
+        self.targets.retain(|t| &t.name != name);
+    }
+
+    /// Points GProfiles at another LGHUB install, takes effect on the next reload.
+    pub fn select_target(&mut self, name: &String) -> Result<()> {
+        let Some(target) = self.targets.iter().find(|t| &t.name == name) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No LGHUB settings named {}.", name),
+            )
+            .into());
+        };
+        store::validate_location(path::Path::new(&target.path))?;
+        self.lghub = Some(target.path.clone());
+        Ok(())
+    }
+
+    /// Roots to scan for a library `source`, when the user configured any.
+    pub fn get_library_roots(&self, source: &str) -> Option<Vec<path::PathBuf>> {
+        self.library_roots
+            .get(source)
+            .filter(|roots| !roots.is_empty())
+            .map(|roots| roots.iter().map(path::PathBuf::from).collect())
+    }
+
+    pub fn get_poster_base(&self) -> Option<String> {
+        self.poster_base.clone()
+    }
+
+    pub fn get_poster_cache(&self) -> Option<path::PathBuf> {
+        self.gprofiles_settings
+            .as_ref()
+            .map(|f| f.with_file_name("posters"))
+    }
+
+    pub fn get_depots_root(&self) -> path::PathBuf {
+        self.depots
+            .as_ref()
+            .map(path::PathBuf::from)
+            .unwrap_or_else(depots::default_root)
+    }
+
+    pub fn get_watched_paths(&self) -> Vec<path::PathBuf> {
+        let mut paths = self.store.files();
+        paths.extend(self.gprofiles_settings.clone());
+        paths
+    }
+
+    pub fn is_stale(&self) -> bool {
+        let lghub_changed = self
+            .store
+            .fingerprint()
+            .map(|fingerprint| fingerprint != self.fingerprint)
+            .unwrap_or(false);
+        let gprofiles_changed = self
+            .gprofiles_settings
+            .as_ref()
+            .and_then(|file| fs::read(file).ok())
+            .is_some_and(|data| utils::fingerprint(&data) != self.gprofiles_fingerprint);
+        lghub_changed || gprofiles_changed
+    }
+
+    fn write_backup(&self) -> Result<Option<Backup>> {
+        let Some(backups) = &self.backups else {
+            return Ok(None);
+        };
+        let backup = backup::create(backups, |dir| {
+            self.store.snapshot(dir)?;
+            if let Some(settings) = &self.gprofiles_settings
+                && let Some(name) = settings.file_name()
+                && settings.exists()
+            {
+                fs::copy(settings, dir.join(name))?;
+            }
+            Ok(())
+        })?;
+        Ok(Some(backup))
+    }
+
+    fn prune_backups(&self) -> Result<()> {
+        if let Some(backups) = &self.backups {
+            let retention = self.backup_retention.unwrap_or(backup::DEFAULT_RETENTION);
+            backup::prune(backups, retention)?;
+        }
+        Ok(())
+    }
+
+    /// Backs up the settings before they are written to, keeping the most recent backups.
+    pub fn backup(&self) -> Result<Option<Backup>> {
+        let backup = self.write_backup()?;
+        self.prune_backups()?;
+        Ok(backup)
+    }
+
+    pub fn get_backups(&self) -> Vec<Backup> {
+        match &self.backups {
+            Some(backups) => backup::list(backups).unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
+    pub fn restore_backup(&self, backup: &Backup) -> Result<()> {
+        // Pruned only once restored, so that the backup being restored is not rotated out
+        self.write_backup()?;
+        self.store.restore(&backup.path)?;
+        if let Some(settings) = &self.gprofiles_settings
+            && let Some(file) = settings
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| backup.file(n))
+        {
+            utils::write_atomic(settings, &fs::read(file)?)?;
         }
+        self.prune_backups()
     }
 
     pub fn get_applications(&self) -> &Vec<Application> {
         &self.applications
     }
 
-    pub fn edit_application<P: Fn(Application) -> Application>(&mut self, id: &String, pred: P) {
+    pub fn edit_application<P: FnOnce(Application) -> Application>(
+        &mut self,
+        id: &String,
+        pred: P,
+    ) {
         if let Some(index) = self.get_application_index(id) {
             let new = pred(self.applications[index].clone());
             self.applications[index] = new;
         }
     }
 
+    /// Paths of custom applications that point to missing files.
+    pub fn find_broken_paths(&self) -> Vec<Relocation> {
+        relocate::find_broken(&self.applications)
+    }
+
+    /// Moves every path that has somewhere to go, returning how many were moved.
+    pub fn relocate(&mut self, relocations: &[Relocation]) -> usize {
+        let mut moved = 0;
+        for relocation in relocations {
+            let Some(to) = &relocation.to else {
+                continue;
+            };
+            self.edit_application(&relocation.application_id, |mut app| {
+                let value = match relocation.field {
+                    Field::Executable => &mut app.applicationPath,
+                    Field::Poster => &mut app.posterPath,
+                };
+                if value.as_ref() == Some(&relocation.from) {
+                    *value = Some(to.clone());
+                    moved += 1;
+                }
+                app
+            });
+        }
+        moved
+    }
+
+    /// Creates a custom application together with its default profile, like LGHUB does.
+    pub fn create_application(
+        &mut self,
+        name: String,
+        executable: String,
+        poster: Option<String>,
+    ) -> String {
+        let application = Application::custom(name, executable, poster.unwrap_or_default());
+        let id = application.applicationId.clone();
+        self.profiles.push(Profile::default_for(id.clone()));
+        self.applications.push(application);
+        id
+    }
+
+    /// Adds a game of LGHUB's database as an installed application, with its default profile.
+    pub fn add_catalog_application(&mut self, game: &CatalogGame) -> Result<String> {
+        if self
+            .find_application_by_database_id(&game.database_id)
+            .is_some()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already has an application.", game.name),
+            )
+            .into());
+        }
+        let application = game.as_application();
+        let id = application.applicationId.clone();
+        self.profiles.push(Profile::default_for(id.clone()));
+        self.applications.push(application);
+        Ok(id)
+    }
+
+    /// Copies an application as a custom one, together with its profiles and keybinds.
+    /// Returns the id of the copy.
+    pub fn duplicate_application(&mut self, id: &String) -> Result<String> {
+        let Some(original) = self.get_application(id) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Application not found.").into());
+        };
+        if original.name == APPLICATION_NAME_DESKTOP {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The Desktop application cannot be duplicated.",
+            )
+            .into());
+        }
+
+        let mut application = original.clone();
+        application.applicationId = Uuid::new_v4().to_string();
+        application.name = utils::copy_name(&original.name, |name| {
+            self.applications.iter().any(|a| a.name == name)
+        });
+        // LGHUB matches installed applications by their database entry, so copies are custom
+        application.isCustom = Some(true);
+        application.databaseId = None;
+        application.version = None;
+        application.applicationFolder = None;
+        application.isInstalled = None;
+        application.posterTitlePosition = None;
+        application.posterUrl = None;
+        // Deleting either application removes its cached image, so the copy gets its own
+        if let Some(poster) = self.get_cached_poster(original)
+            && let Some(icon_cache) = self.get_icon_cache()
+        {
+            let copy = icon_cache.join(Uuid::new_v4().to_string() + ".bmp");
+            fs::copy(&poster, &copy)?;
+            application.posterPath = Some(copy.to_string_lossy().to_string());
+        } else if application.posterPath.is_none() {
+            application.posterPath = Some(String::new());
+        }
+
+        let copy_id = application.applicationId.clone();
+        for mut profile in self.get_profiles_for(id) {
+            profile.id = Uuid::new_v4().to_string();
+            profile.applicationId = copy_id.clone();
+            self.profiles.push(profile);
+        }
+        let keybinds = self.get_keybinds_for(id);
+        if !keybinds.is_empty() {
+            self.keybinds.insert(copy_id.clone(), keybinds);
+        }
+        self.applications.push(application);
+        Ok(copy_id)
+    }
+
+    /// Copies the profile `id`, assignments included, into the application `application_id`.
+    /// Returns the id of the copy, which is not active.
+    pub fn duplicate_profile(&mut self, id: &String, application_id: &String) -> Result<String> {
+        let Some(original) = self.profiles.iter().find(|p| &p.id == id) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Profile not found.").into());
+        };
+        if self.get_application(application_id).is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Application not found.").into());
+        }
+
+        let mut profile = original.clone();
+        // The default profile is shown as "Default", and only one of them may exist
+        let name = if original.name == PROFILE_NAME_DEFAULT {
+            "Default"
+        } else {
+            &original.name
+        };
+        profile.name = utils::copy_name(name, |name| {
+            self.profiles
+                .iter()
+                .any(|p| &p.applicationId == application_id && p.name == name)
+        });
+        profile.id = Uuid::new_v4().to_string();
+        profile.applicationId = application_id.clone();
+        profile.activeForApplication = false;
+
+        let copy_id = profile.id.clone();
+        self.profiles.push(profile);
+        Ok(copy_id)
+    }
+
+    fn get_editable_profile(&self, id: &String) -> Result<&Profile> {
+        let Some(profile) = self.get_profile(id) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Profile not found.").into());
+        };
+        if profile.name == PROFILE_NAME_DEFAULT {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The default profile cannot be renamed or deleted.",
+            )
+            .into());
+        }
+        Ok(profile)
+    }
+
+    /// Renames the profile `id`. Names are unique within an application.
+    pub fn rename_profile(&mut self, id: &String, name: &str) -> Result<()> {
+        let profile = self.get_editable_profile(id)?;
+        let name = name.trim();
+        if name.is_empty() || name == PROFILE_NAME_DEFAULT {
+            return Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "Profile name is not valid.").into(),
+            );
+        }
+        if self
+            .profiles
+            .iter()
+            .any(|p| p.applicationId == profile.applicationId && &p.id != id && p.name == name)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("A profile named {} already exists.", name),
+            )
+            .into());
+        }
+        let name = name.to_string();
+        if let Some(profile) = self.profiles.iter_mut().find(|p| &p.id == id) {
+            profile.name = name;
+        }
+        Ok(())
+    }
+
+    /// Deletes the profile `id`. If it was active, the default profile of its application
+    /// becomes active instead.
+    pub fn delete_profile(&mut self, id: &String) -> Result<()> {
+        let profile = self.get_editable_profile(id)?.clone();
+        self.profiles.retain(|p| &p.id != id);
+        if profile.activeForApplication {
+            let fallback = self
+                .profiles
+                .iter()
+                .filter(|p| p.applicationId == profile.applicationId)
+                .min_by_key(|p| p.name != PROFILE_NAME_DEFAULT)
+                .map(|p| p.id.clone());
+            if let Some(fallback) = fallback {
+                self.activate_profile(&fallback)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes `id` the one active profile of its application.
+    pub fn activate_profile(&mut self, id: &String) -> Result<()> {
+        let Some(application_id) = self
+            .profiles
+            .iter()
+            .find(|p| &p.id == id)
+            .map(|p| p.applicationId.clone())
+        else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Profile not found.").into());
+        };
+        for profile in self
+            .profiles
+            .iter_mut()
+            .filter(|p| p.applicationId == application_id)
+        {
+            profile.activeForApplication = &profile.id == id;
+        }
+        Ok(())
+    }
+
+    pub fn get_duplicate_groups(&self) -> Vec<DuplicateGroup> {
+        duplicates::find_groups(&self.applications)
+    }
+
+    /// Checks that `others` can be merged into `keep`, returning them without repeats or `keep`.
+    pub fn check_merge(&self, keep: &String, others: &[String]) -> Result<Vec<String>> {
+        let mut ids: Vec<String> = vec![];
+        for other in others {
+            if other != keep && !ids.contains(other) {
+                ids.push(other.clone());
+            }
+        }
+        if self.get_application(keep).is_none()
+            || ids.iter().any(|id| self.get_application(id).is_none())
+        {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Application not found.").into());
+        }
+        if ids.iter().chain([keep]).any(|id| {
+            self.get_application(id)
+                .is_some_and(|a| a.name == APPLICATION_NAME_DESKTOP)
+        }) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The Desktop application cannot be merged.",
+            )
+            .into());
+        }
+        // LGHUB would list installed games again, without the profiles moved away from them
+        if ids.iter().any(|id| {
+            self.get_application(id)
+                .is_some_and(|a| a.isCustom.is_none())
+        }) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Only custom applications can be merged into another.",
+            )
+            .into());
+        }
+        Ok(ids)
+    }
+
+    /// Folds the custom applications `others` into `keep`: their profiles move over, keybinds
+    /// are appended unless `keep` already has them, and the applications themselves are removed.
+    /// Returns the images no application uses anymore, for the caller to remove once LGHUB's
+    /// settings no longer refer to them.
+    pub fn merge_applications(
+        &mut self,
+        keep: &String,
+        others: &[String],
+    ) -> Result<Vec<path::PathBuf>> {
+        let others = self.check_merge(keep, others)?;
+        let mut images: Vec<path::PathBuf> = vec![];
+        for other in &others {
+            let application = self.get_application(other).unwrap().clone(); // safe unwrap
+            images.extend(self.get_cached_poster(&application));
+
+            for index in 0..self.profiles.len() {
+                if &self.profiles[index].applicationId != other {
+                    continue;
+                }
+                // `keep` already has a default and an active profile
+                let mut name = self.profiles[index].name.clone();
+                if name == PROFILE_NAME_DEFAULT {
+                    name = application.name.clone();
+                }
+                let base = name.clone();
+                let mut count = 1;
+                while self
+                    .profiles
+                    .iter()
+                    .any(|p| &p.applicationId == keep && p.name == name)
+                {
+                    count += 1;
+                    name = format!("{} ({})", base, count);
+                }
+                let profile = &mut self.profiles[index];
+                profile.name = name;
+                profile.applicationId = keep.clone();
+                profile.activeForApplication = false;
+            }
+
+            let mut keybinds = self.get_keybinds_for(keep);
+            for keybind in self.keybinds.remove(other).unwrap_or_default() {
+                if !keybinds.contains(&keybind) {
+                    keybinds.push(keybind);
+                }
+            }
+            if !keybinds.is_empty() {
+                self.keybinds.insert(keep.clone(), keybinds);
+            }
+            self.applications.retain(|a| &a.applicationId != other);
+        }
+
+        let kept = self
+            .get_application(keep)
+            .and_then(|a| self.get_cached_poster(a));
+        images.retain(|i| Some(i) != kept.as_ref());
+        Ok(images)
+    }
+
+    /// Everything that `delete_application` would remove.
+    pub fn preview_deletion(&self, id: &String) -> Result<Deletion> {
+        let Some(application) = self.get_application(id) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Application not found.").into());
+        };
+        if application.isCustom.is_none() || application.name == APPLICATION_NAME_DESKTOP {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Only custom applications can be deleted.",
+            )
+            .into());
+        }
+
+        let images: Vec<path::PathBuf> = self.get_cached_poster(application).into_iter().collect();
+        Ok(Deletion {
+            application: application.clone(),
+            profiles: self.get_profiles_for(id),
+            keybinds: self.get_keybinds_for(id),
+            images,
+        })
+    }
+
+    /// Removes the application `id`. Its images are left for the caller to remove once
+    /// LGHUB's settings no longer refer to them.
+    pub fn delete_application(&mut self, id: &String) -> Result<Deletion> {
+        let deletion = self.preview_deletion(id)?;
+        self.profiles.retain(|p| &p.applicationId != id);
+        self.keybinds.remove(id);
+        self.applications.retain(|a| &a.applicationId != id);
+        Ok(deletion)
+    }
+
     pub fn replace_application(&mut self, id: &String, app: Application) {
         if let Some(index) = self.get_application_index(id) {
             self.applications[index] = app;
@@ -127,6 +859,20 @@ impl Config {
         self.applications.iter().find(|a| &a.applicationId == id)
     }
 
+    pub fn find_application_by_executable(&self, executable: &path::Path) -> Option<&Application> {
+        self.applications.iter().find(|a| {
+            a.applicationPath
+                .as_ref()
+                .is_some_and(|p| duplicates::same_executable(p, executable))
+        })
+    }
+
+    pub fn find_application_by_database_id(&self, database_id: &str) -> Option<&Application> {
+        self.applications
+            .iter()
+            .find(|a| a.databaseId.as_deref() == Some(database_id))
+    }
+
     pub fn get_desktop_application(&self) -> Option<&Application> {
         self.applications
             .iter()
@@ -140,6 +886,10 @@ impl Config {
         }
     }
 
+    pub fn get_profile(&self, id: &String) -> Option<&Profile> {
+        self.profiles.iter().find(|p| &p.id == id)
+    }
+
     pub fn get_profiles_for(&self, id: &String) -> Vec<Profile> {
         let mut profiles: Vec<Profile> = vec![];
         for prof in &self.profiles {
@@ -151,12 +901,355 @@ impl Config {
     }
 
     pub fn get_icon_cache(&self) -> Option<path::PathBuf> {
+        if self.lghub_location.as_os_str().is_empty() {
+            return None;
+        }
         Some(self.lghub_location.join("icon_cache"))
     }
 
+    /// The poster of `application`, if GProfiles saved it into LGHUB's icon cache.
+    fn get_cached_poster(&self, application: &Application) -> Option<path::PathBuf> {
+        let icon_cache = self.get_icon_cache()?;
+        let poster = path::PathBuf::from(application.posterPath.as_ref()?);
+        (poster.parent() == Some(icon_cache.as_path())
+            && poster.extension().is_some_and(|e| e == "bmp")
+            && poster.exists())
+        .then_some(poster)
+    }
+
     fn get_application_index(&self, id: &String) -> Option<usize> {
         self.applications
             .iter()
             .position(|a| &a.applicationId == id)
     }
 }
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::fallback(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path, slice};
+
+    use serde_json::{Value, json};
+
+    use super::Config;
+    use crate::{
+        merge::Resolution,
+        store::{self, MemoryStore},
+        utils,
+    };
+
+    // LGHUB settings with the Desktop and a custom game, which has a second profile
+    fn document() -> Vec<u8> {
+        let profile = |application: &str, id: &str, name: &str, active: bool| {
+            json!({
+                "activeForApplication": active,
+                "applicationId": application,
+                "id": id,
+                "name": name,
+                "assignments": [],
+            })
+        };
+        serde_json::to_vec(&json!({
+            "applications": {
+                "applications": [
+                    { "name": "APPLICATION_NAME_DESKTOP", "applicationId": "desktop" },
+                    {
+                        "name": "Game",
+                        "applicationId": "game",
+                        "applicationPath": "C:\\Games\\game.exe",
+                        "isCustom": true,
+                        "posterPath": "",
+                    },
+                ],
+            },
+            "profiles": {
+                "profiles": [
+                    profile("desktop", "desktop-default", "PROFILE_NAME_DEFAULT", true),
+                    profile("game", "game-default", "PROFILE_NAME_DEFAULT", true),
+                    profile("game", "game-racing", "Racing", false),
+                ],
+            },
+            "cards": { "cards": [] },
+        }))
+        .unwrap()
+    }
+
+    fn config() -> Config {
+        let store = Box::new(MemoryStore::new(document()));
+        Config::with_store(store, None, path::PathBuf::new()).unwrap()
+    }
+
+    fn saved(config: &Config) -> Value {
+        serde_json::from_slice(&config.store.read().unwrap()).unwrap()
+    }
+
+    // Ids of the active profiles of `application`
+    fn active(config: &Config, application: &str) -> Vec<String> {
+        config
+            .get_profiles_for(&String::from(application))
+            .into_iter()
+            .filter(|p| p.activeForApplication)
+            .map(|p| p.id)
+            .collect()
+    }
+
+    #[test]
+    fn default_profile_cannot_be_renamed_or_deleted() {
+        let mut config = config();
+        let default = String::from("game-default");
+        assert!(config.rename_profile(&default, "Renamed").is_err());
+        assert!(config.delete_profile(&default).is_err());
+        assert_eq!(
+            config.get_profile(&default).unwrap().name,
+            "PROFILE_NAME_DEFAULT"
+        );
+    }
+
+    #[test]
+    fn deleting_active_profile_activates_default() {
+        let mut config = config();
+        let racing = String::from("game-racing");
+        config.activate_profile(&racing).unwrap();
+        config.delete_profile(&racing).unwrap();
+        assert!(config.get_profile(&racing).is_none());
+        assert_eq!(active(&config, "game"), ["game-default"]);
+    }
+
+    #[test]
+    fn activating_leaves_one_active_profile() {
+        let mut config = config();
+        let (game, racing) = (String::from("game"), String::from("game-racing"));
+        let copy = config.duplicate_profile(&racing, &game).unwrap();
+        for id in [&racing, &copy, &String::from("game-default")] {
+            config.activate_profile(id).unwrap();
+            assert_eq!(active(&config, "game"), slice::from_ref(id));
+            assert_eq!(active(&config, "desktop"), ["desktop-default"]);
+        }
+        assert!(config.activate_profile(&String::from("missing")).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_and_empty_names() {
+        let mut config = config();
+        let (game, racing) = (String::from("game"), String::from("game-racing"));
+        let copy = config.duplicate_profile(&racing, &game).unwrap();
+        for name in ["Racing", " Racing ", "", "  ", "PROFILE_NAME_DEFAULT"] {
+            assert!(config.rename_profile(&copy, name).is_err(), "{:?}", name);
+        }
+        assert_ne!(config.get_profile(&copy).unwrap().name, "Racing");
+        config.rename_profile(&copy, "Drifting").unwrap();
+        assert!(config.rename_profile(&racing, "Drifting ").is_err());
+        // A profile keeps its own name, and names are trimmed
+        config.rename_profile(&racing, " Racing ").unwrap();
+        assert_eq!(config.get_profile(&racing).unwrap().name, "Racing");
+    }
+
+    #[test]
+    fn saves_into_memory_store_with_outside_changes() {
+        let mut config = config();
+        let id = config.create_application(String::from("New"), String::from("C:\\new.exe"), None);
+        // LGHUB writes to its settings in the meantime
+        config
+            .store
+            .update(&mut |data| {
+                let mut document: Value = serde_json::from_slice(&data)?;
+                document["cards"]["cards"] = json!([{ "id": "card" }]);
+                Ok(serde_json::to_vec(&document)?)
+            })
+            .unwrap();
+        config.save(Resolution::Report).unwrap();
+
+        let document = saved(&config);
+        let applications = document["applications"]["applications"].as_array().unwrap();
+        assert!(
+            applications
+                .iter()
+                .any(|a| a["applicationId"] == id.as_str())
+        );
+        assert_eq!(document["cards"]["cards"], json!([{ "id": "card" }]));
+        assert!(config.get_application(&id).is_some());
+    }
+
+    #[test]
+    fn saves_into_json_store() {
+        let dir = utils::temp_dir();
+        fs::write(dir.join("settings.json"), document()).unwrap();
+        let mut config = Config::with_store(store::open(&dir), None, dir.clone()).unwrap();
+        config.edit_application(&String::from("game"), |mut application| {
+            application.name = String::from("Renamed");
+            application
+        });
+        config.save(Resolution::Report).unwrap();
+
+        let reloaded = Config::with_store(store::open(&dir), None, dir.clone()).unwrap();
+        let application = reloaded.get_application(&String::from("game")).unwrap();
+        assert_eq!(application.name, "Renamed");
+        assert!(!dir.join("settings.db").exists());
+    }
+
+    #[test]
+    fn fallback_is_not_saved() {
+        let mut config = Config::default();
+        config.create_application(String::from("New"), String::from("C:\\new.exe"), None);
+        assert!(config.save(Resolution::Report).is_err());
+        assert!(!saved(&config).to_string().contains("New"));
+    }
+
+    #[test]
+    fn fallback_can_pick_another_location() {
+        let dir = utils::temp_dir();
+        let settings = dir.join("settings.json");
+        fs::write(&settings, "{}").unwrap();
+        let lghub = dir.join("LGHUB");
+        fs::create_dir(&lghub).unwrap();
+        fs::write(lghub.join("settings.json"), document()).unwrap();
+
+        let mut config = Config::fallback(Some(settings.clone()));
+        assert!(config.select_target(&String::from("LGHUB")).is_err());
+        let target = config.add_target(&lghub).unwrap();
+        config.select_target(&target.name).unwrap();
+        config.save_gprofiles().unwrap();
+        let saved: Value = serde_json::from_slice(&fs::read(&settings).unwrap()).unwrap();
+        assert_eq!(saved["lghub"], lghub.to_string_lossy().as_ref());
+
+        // Without a place for GProfiles' settings, the change is not silently dropped
+        assert!(Config::default().save_gprofiles().is_err());
+    }
+
+    #[test]
+    fn relocates_missing_paths_of_custom_applications() {
+        let dir = utils::temp_dir();
+        let poster = dir.join("poster.png");
+        fs::write(&poster, b"").unwrap();
+        let mut config = config();
+        let missing = String::from("C:\\Games\\game.exe");
+        let moved = config.create_application(
+            String::from("Moved"),
+            missing.clone(),
+            Some(poster.to_string_lossy().to_string()),
+        );
+        config.edit_application(&String::from("game"), |mut application| {
+            application.posterPath = Some(String::from("C:\\Posters\\game.png"));
+            application
+        });
+        let installed = config.create_application(String::from("Installed"), missing.clone(), None);
+        config.edit_application(&installed, |mut application| {
+            application.isCustom = None;
+            application
+        });
+
+        // Installed applications, empty paths and existing files are left alone
+        let mut broken = config.find_broken_paths();
+        let found: Vec<(&str, &str)> = broken
+            .iter()
+            .map(|r| (r.application_id.as_str(), r.field.label()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("game", "Executable"),
+                ("game", "Image"),
+                (moved.as_str(), "Executable"),
+            ]
+        );
+
+        broken[2].to = Some(String::from("D:\\game.exe"));
+        assert_eq!(config.relocate(&broken), 1);
+        let application = config.get_application(&moved).unwrap();
+        assert_eq!(application.applicationPath.as_deref(), Some("D:\\game.exe"));
+        assert_eq!(config.find_broken_paths().len(), 3);
+    }
+
+    #[test]
+    fn deleting_leaves_images_to_the_caller() {
+        let dir = utils::temp_dir();
+        fs::create_dir(dir.join("icon_cache")).unwrap();
+        let poster = dir.join("icon_cache").join("poster.bmp");
+        fs::write(&poster, b"BM").unwrap();
+        let store = Box::new(MemoryStore::new(document()));
+        let mut config = Config::with_store(store, None, dir).unwrap();
+        let game = String::from("game");
+        config.edit_application(&game, |mut application| {
+            application.posterPath = Some(poster.to_string_lossy().to_string());
+            application
+        });
+
+        assert!(config.delete_application(&String::from("desktop")).is_err());
+        let deletion = config.delete_application(&game).unwrap();
+        assert_eq!(deletion.images, vec![poster.clone()]);
+        assert_eq!(deletion.profiles.len(), 2);
+        assert!(poster.exists());
+        assert!(config.get_application(&game).is_none());
+        assert!(config.get_profiles_for(&game).is_empty());
+    }
+
+    #[test]
+    fn merging_leaves_images_to_the_caller() {
+        let dir = utils::temp_dir();
+        fs::create_dir(dir.join("icon_cache")).unwrap();
+        let poster = dir.join("icon_cache").join("poster.bmp");
+        fs::write(&poster, b"BM").unwrap();
+        let store = Box::new(MemoryStore::new(document()));
+        let mut config = Config::with_store(store, None, dir).unwrap();
+        let game = String::from("game");
+        config.edit_application(&game, |mut application| {
+            application.posterPath = Some(poster.to_string_lossy().to_string());
+            application
+        });
+        let executable = String::from("C:\\Games\\game.exe");
+        let copy = config.create_application(String::from("Copy"), executable.clone(), None);
+        let installed = config.create_application(String::from("Installed"), executable, None);
+        config.edit_application(&installed, |mut application| {
+            application.isCustom = None;
+            application
+        });
+
+        assert!(
+            config
+                .merge_applications(&copy, slice::from_ref(&installed))
+                .is_err()
+        );
+        // Repeating an application, or listing `keep` itself, merges it once
+        let images = config
+            .merge_applications(&copy, &[game.clone(), copy.clone(), game.clone()])
+            .unwrap();
+        assert_eq!(images, vec![poster.clone()]);
+        assert!(poster.exists());
+        assert!(config.get_application(&game).is_none());
+        assert!(config.get_application(&installed).is_some());
+        let profiles = config.get_profiles_for(&copy);
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Game", "Racing", "PROFILE_NAME_DEFAULT"]);
+        let active: Vec<&str> = profiles
+            .iter()
+            .filter(|p| p.activeForApplication)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(active, ["PROFILE_NAME_DEFAULT"]);
+    }
+
+    #[test]
+    fn backs_up_before_every_save() {
+        let dir = utils::temp_dir();
+        let settings = dir.join("settings.json");
+        fs::write(&settings, "{}").unwrap();
+        let store = Box::new(MemoryStore::default());
+        let mut config = Config::with_store(store, Some(settings), dir.clone()).unwrap();
+        config.backup_retention = Some(2);
+
+        config.save(Resolution::Report).unwrap();
+        assert_eq!(config.get_backups().len(), 1);
+        config.save(Resolution::Report).unwrap();
+        config.save(Resolution::Report).unwrap();
+        // Older backups are pruned, even within the same millisecond
+        let backups = config.get_backups();
+        assert_eq!(backups.len(), 2);
+        assert!(backups[0].created > backups[1].created);
+        assert!(backups[0].file("settings.json").is_some());
+    }
+}
@@ -0,0 +1,98 @@
+use std::{cmp::Ordering, collections::HashMap, env, fs, io, path};
+
+use crate::error::Result;
+
+const IMAGES: [&str; 2] = ["core_apps", "images"];
+
+/// Where LGHUB installs its depots unless configured otherwise.
+pub fn default_root() -> path::PathBuf {
+    let program_data = env::var_os("PROGRAMDATA").unwrap_or("C:\\ProgramData".into());
+    let mut root = path::PathBuf::from(program_data);
+    root.extend(["LGHUB", "depots"]);
+    root
+}
+
+/// The numbers of a depot's version name, e.g. `[2024, 3, 512345]` for `2024.3.512345`.
+pub fn parse_version(name: &str) -> Vec<u64> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    parse_version(a)
+        .cmp(&parse_version(b))
+        .then_with(|| a.cmp(b))
+}
+
+// Depots copied from Windows keep their names, but not necessarily their case
+fn find_child(dir: &path::Path, name: &str) -> Option<path::PathBuf> {
+    let exact = dir.join(name);
+    if exact.exists() {
+        return Some(exact);
+    }
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .find(|e| e.file_name().to_string_lossy().eq_ignore_ascii_case(name))
+        .map(|e| e.path())
+}
+
+fn find_path(dir: &path::Path, children: &[&str]) -> Option<path::PathBuf> {
+    children
+        .iter()
+        .try_fold(dir.to_path_buf(), |dir, child| find_child(&dir, child))
+}
+
+/// The newest version of LGHUB's depots, and the images it ships.
+#[derive(Debug)]
+pub struct Depot {
+    root: path::PathBuf,
+    path: path::PathBuf,
+    images: HashMap<String, path::PathBuf>,
+}
+
+impl Depot {
+    /// Picks the newest depot under `root` by its version name, and indexes its images.
+    pub fn open(root: &path::Path) -> Result<Self> {
+        let newest = fs::read_dir(root)?
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            .filter(|(name, _)| !parse_version(name).is_empty())
+            .max_by(|(a, _), (b, _)| compare_versions(a, b));
+        let Some((_, path)) = newest else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No LGHUB depots found.").into());
+        };
+
+        let mut images = HashMap::new();
+        if let Some(dir) = find_path(&path, &IMAGES) {
+            for entry in fs::read_dir(dir)?.flatten() {
+                if entry.path().is_file() {
+                    let name = entry.file_name().to_string_lossy().to_lowercase();
+                    images.insert(name, entry.path());
+                }
+            }
+        }
+        Ok(Depot {
+            root: root.to_path_buf(),
+            path,
+            images,
+        })
+    }
+
+    pub fn root(&self) -> &path::Path {
+        &self.root
+    }
+
+    /// A file of the depot, matching the names of `children` regardless of case.
+    pub fn find(&self, children: &[&str]) -> Option<path::PathBuf> {
+        find_path(&self.path, children)
+    }
+
+    /// An image shipped with the depot, by file name.
+    pub fn image(&self, name: &str) -> Option<&path::Path> {
+        self.images.get(&name.to_lowercase()).map(|p| p.as_path())
+    }
+}
@@ -0,0 +1,174 @@
+use std::{fs, path};
+
+use crate::types::logitech::Application;
+
+/// A comparable form of an executable path: symlinks are resolved when the file exists,
+/// and case, separators and `.` components are ignored like Windows does.
+pub fn normalize_executable(value: &str) -> String {
+    let resolved = fs::canonicalize(value)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(value.to_string());
+    // Canonical paths on Windows are verbatim
+    let resolved = resolved.strip_prefix("\\\\?\\").unwrap_or(&resolved);
+    resolved
+        .split(['\\', '/'])
+        .enumerate()
+        .filter(|(i, part)| *i == 0 || (!part.is_empty() && *part != "."))
+        .map(|(_, part)| part.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Applications that point to the same executable.
+#[derive(Debug)]
+pub struct DuplicateGroup {
+    pub executable: String,
+    pub applications: Vec<Application>,
+}
+
+impl DuplicateGroup {
+    /// The application the others are merged into: the installed one if there is any, as
+    /// installed applications cannot be merged away, then `active`, then the first one.
+    pub fn keep(&self, active: &str) -> &Application {
+        self.applications
+            .iter()
+            .find(|a| a.isCustom.is_none())
+            .or(self.applications.iter().find(|a| a.applicationId == active))
+            .unwrap_or(&self.applications[0])
+    }
+}
+
+impl Clone for DuplicateGroup {
+    fn clone(&self) -> Self {
+        DuplicateGroup {
+            executable: self.executable.clone(),
+            applications: self.applications.clone(),
+        }
+    }
+}
+
+/// Groups of two or more applications sharing an executable, in order of first appearance.
+/// Groups with more than one installed application are left out, as those cannot be merged.
+pub fn find_groups(applications: &[Application]) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<DuplicateGroup> = vec![];
+    for application in applications {
+        let Some(executable) = application
+            .applicationPath
+            .as_ref()
+            .filter(|p| !p.is_empty())
+        else {
+            continue;
+        };
+        let executable = normalize_executable(executable);
+        match groups.iter_mut().find(|g| g.executable == executable) {
+            Some(group) => group.applications.push(application.clone()),
+            None => groups.push(DuplicateGroup {
+                executable,
+                applications: vec![application.clone()],
+            }),
+        }
+    }
+    groups.retain(|g| {
+        g.applications.len() > 1
+            && g.applications
+                .iter()
+                .filter(|a| a.isCustom.is_none())
+                .count()
+                <= 1
+    });
+    groups
+}
+
+/// Whether `a` and `b` point to the same executable.
+pub fn same_executable(a: &str, b: &path::Path) -> bool {
+    normalize_executable(a) == normalize_executable(&b.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::{find_groups, normalize_executable, same_executable};
+    use crate::{types::logitech::Application, utils};
+
+    fn application(id: &str, executable: &str, custom: bool) -> Application {
+        let mut application: Application = serde_json::from_value(json!({
+            "name": id,
+            "applicationId": id,
+            "applicationPath": executable,
+        }))
+        .unwrap();
+        application.isCustom = custom.then_some(true);
+        application
+    }
+
+    #[test]
+    fn normalizes_like_windows() {
+        assert_eq!(
+            normalize_executable("C:\\Games\\.\\Game\\\\GAME.exe"),
+            "c:/games/game/game.exe"
+        );
+        assert_eq!(
+            normalize_executable("C:/Games/Game/game.exe"),
+            normalize_executable("c:\\games\\game\\Game.EXE")
+        );
+        assert_ne!(
+            normalize_executable("C:\\Games\\game.exe"),
+            normalize_executable("D:\\Games\\game.exe")
+        );
+    }
+
+    #[test]
+    fn resolves_existing_files() {
+        let dir = utils::temp_dir();
+        let file = dir.join("game.exe");
+        fs::write(&file, b"").unwrap();
+        let indirect = dir.join(".").join("game.exe");
+        assert!(same_executable(&indirect.to_string_lossy(), &file));
+    }
+
+    #[test]
+    fn groups_applications_by_executable() {
+        let applications = vec![
+            application("a", "C:\\Games\\game.exe", true),
+            application("other", "C:\\Games\\other.exe", true),
+            application("b", "c:/games/GAME.exe", true),
+            application("installed", "C:\\Games\\game.exe", false),
+            application("empty", "", true),
+            application("also-empty", "", true),
+        ];
+        let groups = find_groups(&applications);
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0]
+            .applications
+            .iter()
+            .map(|a| a.applicationId.as_str())
+            .collect();
+        assert_eq!(ids, ["a", "b", "installed"]);
+        // The installed application is kept, whichever is active
+        assert_eq!(groups[0].keep("b").applicationId, "installed");
+    }
+
+    #[test]
+    fn keeps_active_then_first_custom_application() {
+        let applications = vec![
+            application("a", "C:\\game.exe", true),
+            application("b", "C:\\game.exe", true),
+        ];
+        let groups = find_groups(&applications);
+        assert_eq!(groups[0].keep("b").applicationId, "b");
+        assert_eq!(groups[0].keep("unknown").applicationId, "a");
+    }
+
+    #[test]
+    fn leaves_out_groups_of_installed_applications() {
+        let applications = vec![
+            application("a", "C:\\game.exe", false),
+            application("b", "C:\\game.exe", false),
+            application("c", "C:\\game.exe", true),
+        ];
+        assert!(find_groups(&applications).is_empty());
+    }
+}
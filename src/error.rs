@@ -0,0 +1,68 @@
+use std::{error::Error, fmt, io};
+
+use crate::merge::MergeConflicts;
+
+pub type Result<T> = std::result::Result<T, GProfilesError>;
+
+#[derive(Debug)]
+pub enum GProfilesError {
+    Io(io::Error),
+    Sqlite(rusqlite::Error),
+    Json(serde_json::Error),
+    Image(image::ImageError),
+    Conflict(MergeConflicts),
+}
+
+impl fmt::Display for GProfilesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GProfilesError::Io(e) => write!(f, "I/O error: {}", e),
+            GProfilesError::Sqlite(e) => write!(f, "LGHUB database error: {}", e),
+            GProfilesError::Json(e) => write!(f, "Invalid settings: {}", e),
+            GProfilesError::Image(e) => write!(f, "Invalid image: {}", e),
+            GProfilesError::Conflict(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for GProfilesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GProfilesError::Io(e) => Some(e),
+            GProfilesError::Sqlite(e) => Some(e),
+            GProfilesError::Json(e) => Some(e),
+            GProfilesError::Image(e) => Some(e),
+            GProfilesError::Conflict(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for GProfilesError {
+    fn from(value: io::Error) -> Self {
+        GProfilesError::Io(value)
+    }
+}
+
+impl From<rusqlite::Error> for GProfilesError {
+    fn from(value: rusqlite::Error) -> Self {
+        GProfilesError::Sqlite(value)
+    }
+}
+
+impl From<serde_json::Error> for GProfilesError {
+    fn from(value: serde_json::Error) -> Self {
+        GProfilesError::Json(value)
+    }
+}
+
+impl From<image::ImageError> for GProfilesError {
+    fn from(value: image::ImageError) -> Self {
+        GProfilesError::Image(value)
+    }
+}
+
+impl From<MergeConflicts> for GProfilesError {
+    fn from(value: MergeConflicts) -> Self {
+        GProfilesError::Conflict(value)
+    }
+}
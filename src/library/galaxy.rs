@@ -0,0 +1,155 @@
+use std::path;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::{
+    error::Result,
+    library::{InstalledGame, LibrarySource, env_path, find_executable},
+};
+
+const SOURCE: &str = "gog";
+const LABEL: &str = "GOG Galaxy";
+
+// Installed products with their title and the executable of their primary play task
+const INSTALLED_GAMES: &str =
+    "SELECT p.productId, p.installationPath, MAX(d.title), MAX(l.executablePath)
+    FROM InstalledBaseProducts p
+    LEFT JOIN LimitedDetails d ON d.productId = p.productId
+    LEFT JOIN PlayTasks t ON t.gameReleaseKey = 'gog_' || p.productId AND t.isPrimary = 1
+    LEFT JOIN PlayTaskLaunchParameters l ON l.playTaskId = t.id
+    GROUP BY p.productId;";
+
+/// Installed games of a `galaxy-2.0.db`.
+fn read_library(database: &path::Path) -> Result<Vec<InstalledGame>> {
+    let conn = Connection::open_with_flags(database, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement = conn.prepare(INSTALLED_GAMES)?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut games = vec![];
+    for row in rows {
+        let (id, install_dir, title, executable) = row?;
+        let install_dir = path::PathBuf::from(install_dir);
+        let name = title.unwrap_or_else(|| {
+            install_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let executable = match executable {
+            Some(executable) => Some(path::PathBuf::from(executable)).filter(|e| e.is_file()),
+            None => find_executable(&install_dir, &name),
+        };
+        // GOG installers leave the game's icon next to it
+        let artwork = Some(install_dir.join(format!("goggame-{}.ico", id))).filter(|a| a.is_file());
+        games.push(InstalledGame {
+            id: format!("{}:{}", SOURCE, id),
+            source: String::from(LABEL),
+            name,
+            install_dir,
+            executable,
+            artwork,
+        });
+    }
+    Ok(games)
+}
+
+pub struct GogGalaxy;
+
+impl LibrarySource for GogGalaxy {
+    fn name(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn default_roots(&self) -> Vec<path::PathBuf> {
+        env_path("PROGRAMDATA", &["GOG.com", "Galaxy", "storage"])
+            .into_iter()
+            .collect()
+    }
+
+    fn scan(&self, roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+        let mut games: Vec<InstalledGame> = vec![];
+        for database in roots.iter().map(|r| r.join("galaxy-2.0.db")) {
+            if !database.is_file() {
+                continue;
+            }
+            for game in read_library(&database)? {
+                if !games.iter().any(|g| g.id == game.id) {
+                    games.push(game);
+                }
+            }
+        }
+        games.sort_by_key(|g| g.name.to_lowercase());
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::GogGalaxy;
+    use crate::{
+        library::{LibrarySource, touch},
+        utils,
+    };
+
+    #[test]
+    fn scans_installed_products() {
+        let root = utils::temp_dir();
+        let games = root.join("Games");
+        let (tasked, untasked) = (games.join("Tasked"), games.join("Untasked Game"));
+        touch(&tasked.join("bin/tasked.exe"));
+        touch(&tasked.join("goggame-1207658924.ico"));
+        touch(&untasked.join("untasked game.exe"));
+        touch(&untasked.join("unins000.exe"));
+
+        let conn = Connection::open(root.join("galaxy-2.0.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE InstalledBaseProducts (productId INTEGER, installationPath TEXT);
+            CREATE TABLE LimitedDetails (productId INTEGER, title TEXT);
+            CREATE TABLE PlayTasks (id INTEGER, gameReleaseKey TEXT, isPrimary INTEGER);
+            CREATE TABLE PlayTaskLaunchParameters (playTaskId INTEGER, executablePath TEXT);
+            INSERT INTO LimitedDetails VALUES (1207658924, 'Tasked Game');
+            INSERT INTO PlayTasks VALUES (1, 'gog_1207658924', 0), (2, 'gog_1207658924', 1);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO InstalledBaseProducts VALUES (1207658924, ?1), (2, ?2);",
+            [tasked.to_str(), untasked.to_str()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO PlayTaskLaunchParameters VALUES (1, 'launcher.exe'), (2, ?1);",
+            [tasked.join("bin/tasked.exe").to_str()],
+        )
+        .unwrap();
+        drop(conn);
+
+        let found = GogGalaxy
+            .scan(&[root.join("missing"), root.clone()])
+            .unwrap();
+        let ids: Vec<&str> = found.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, ["gog:1207658924", "gog:2"]);
+
+        assert_eq!(found[0].name, "Tasked Game");
+        assert_eq!(found[0].executable, Some(tasked.join("bin/tasked.exe")));
+        assert_eq!(
+            found[0].artwork,
+            Some(tasked.join("goggame-1207658924.ico"))
+        );
+        // Without details or play tasks, the directory names the game and is searched
+        assert_eq!(found[1].name, "Untasked Game");
+        assert_eq!(
+            found[1].executable,
+            Some(untasked.join("untasked game.exe"))
+        );
+        assert_eq!(found[1].artwork, None);
+    }
+}
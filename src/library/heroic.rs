@@ -0,0 +1,256 @@
+use std::{collections::HashMap, fs, path};
+
+use serde_json::Value;
+
+use crate::{
+    error::Result,
+    library::{InstalledGame, LibrarySource, env_path, find_executable},
+};
+
+const SOURCE: &str = "heroic";
+const LABEL: &str = "Heroic";
+
+// Library of each store Heroic supports, relative to its configuration directory
+const LIBRARIES: [&str; 4] = [
+    "store_cache/legendary_library.json",
+    "store_cache/gog_library.json",
+    "store_cache/nile_library.json",
+    "sideload_apps/library.json",
+];
+// Where the stores record installations, when the library itself does not
+const INSTALLATIONS: [&str; 3] = [
+    "legendaryConfig/legendary/installed.json",
+    "gog_store/installed.json",
+    "nile_config/nile/installed.json",
+];
+
+struct Installation {
+    path: path::PathBuf,
+    executable: Option<String>,
+}
+
+impl Clone for Installation {
+    fn clone(&self) -> Self {
+        Installation {
+            path: self.path.clone(),
+            executable: self.executable.clone(),
+        }
+    }
+}
+
+fn read_json(file: &path::Path) -> Result<Option<Value>> {
+    if !file.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&fs::read(file)?)?))
+}
+
+fn installation_of(value: &Value) -> Option<Installation> {
+    let path = value.get("install_path")?.as_str()?;
+    Some(Installation {
+        path: path::PathBuf::from(path),
+        executable: value
+            .get("executable")
+            .and_then(|e| e.as_str())
+            .filter(|e| !e.is_empty())
+            .map(String::from),
+    })
+}
+
+/// Installations recorded by the stores, by app name.
+fn read_installations(root: &path::Path) -> Result<HashMap<String, Installation>> {
+    let mut installations = HashMap::new();
+    for file in INSTALLATIONS {
+        let Some(document) = read_json(&root.join(file))? else {
+            continue;
+        };
+        // Legendary maps app names to installations, the GOG store keeps a list
+        let entries: Vec<(String, &Value)> = match document.get("installed") {
+            Some(Value::Array(list)) => list
+                .iter()
+                .filter_map(|i| Some((i.get("appName")?.as_str()?.to_string(), i)))
+                .collect(),
+            _ => document
+                .as_object()
+                .map(|map| map.iter().map(|(k, v)| (k.clone(), v)).collect())
+                .unwrap_or_default(),
+        };
+        for (name, value) in entries {
+            if let Some(installation) = installation_of(value) {
+                installations.insert(name, installation);
+            }
+        }
+    }
+    Ok(installations)
+}
+
+// Artwork is usually a URL, only local images can become posters
+fn local_artwork(game: &Value) -> Option<path::PathBuf> {
+    ["art_square", "art_cover"]
+        .iter()
+        .filter_map(|key| game.get(key)?.as_str())
+        .map(|art| path::PathBuf::from(art.strip_prefix("file://").unwrap_or(art)))
+        .find(|art| art.is_file())
+}
+
+/// Reads the installed games of one of Heroic's library files.
+fn read_library(
+    file: &path::Path,
+    installations: &HashMap<String, Installation>,
+) -> Result<Vec<InstalledGame>> {
+    let Some(document) = read_json(file)? else {
+        return Ok(vec![]);
+    };
+    let games = document
+        .get("library")
+        .or(document.get("games"))
+        .and_then(|g| g.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut installed = vec![];
+    for game in games {
+        let (Some(app_name), Some(title)) = (
+            game.get("app_name").and_then(|a| a.as_str()),
+            game.get("title").and_then(|t| t.as_str()),
+        ) else {
+            continue;
+        };
+        if game.get("is_installed").and_then(|i| i.as_bool()) != Some(true) {
+            continue;
+        }
+        let Some(installation) = game
+            .get("install")
+            .and_then(installation_of)
+            .or_else(|| installations.get(app_name).cloned())
+        else {
+            continue;
+        };
+        // Executables are relative to the install directory, except for sideloaded apps
+        let executable = match &installation.executable {
+            Some(executable) => Some(installation.path.join(executable)).filter(|e| e.is_file()),
+            None => find_executable(&installation.path, title),
+        };
+        installed.push(InstalledGame {
+            id: format!("{}:{}", SOURCE, app_name),
+            source: String::from(LABEL),
+            name: title.to_string(),
+            install_dir: installation.path,
+            executable,
+            artwork: local_artwork(&game),
+        });
+    }
+    Ok(installed)
+}
+
+pub struct Heroic;
+
+impl LibrarySource for Heroic {
+    fn name(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn default_roots(&self) -> Vec<path::PathBuf> {
+        [
+            env_path("APPDATA", &["heroic"]),
+            env_path("XDG_CONFIG_HOME", &["heroic"]),
+            env_path("HOME", &[".config", "heroic"]),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn scan(&self, roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+        let mut games: Vec<InstalledGame> = vec![];
+        for root in roots.iter().filter(|r| r.is_dir()) {
+            let installations = read_installations(root)?;
+            for file in LIBRARIES {
+                for game in read_library(&root.join(file), &installations)? {
+                    if !games.iter().any(|g| g.id == game.id) {
+                        games.push(game);
+                    }
+                }
+            }
+        }
+        games.sort_by_key(|g| g.name.to_lowercase());
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path};
+
+    use serde_json::{Value, json};
+
+    use super::Heroic;
+    use crate::{
+        library::{LibrarySource, touch},
+        utils,
+    };
+
+    fn write(file: path::PathBuf, document: Value) {
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, document.to_string()).unwrap();
+    }
+
+    #[test]
+    fn scans_every_store() {
+        let root = utils::temp_dir();
+        let games = root.join("Games");
+        let (epic, gog, sideloaded) = (
+            games.join("Epic"),
+            games.join("GOG Game"),
+            games.join("Sideloaded"),
+        );
+        touch(&epic.join("Binaries/epic.exe"));
+        touch(&gog.join("GOG Game.exe"));
+        touch(&gog.join("unins000.exe"));
+        touch(&sideloaded.join("run.exe"));
+        touch(&root.join("art/gog.jpg"));
+
+        // Epic and GOG games are installed according to their store, sideloaded apps in place
+        write(
+            root.join("store_cache/legendary_library.json"),
+            json!({ "library": [
+                { "app_name": "Epic", "title": "Epic Game", "is_installed": true,
+                  "art_square": "https://example.com/epic.jpg" },
+                { "app_name": "Owned", "title": "Owned Game", "is_installed": false },
+            ]}),
+        );
+        write(
+            root.join("legendaryConfig/legendary/installed.json"),
+            json!({ "Epic": { "install_path": epic, "executable": "Binaries/epic.exe" } }),
+        );
+        write(
+            root.join("store_cache/gog_library.json"),
+            json!({ "games": [
+                { "app_name": "1207658924", "title": "GOG Game", "is_installed": true,
+                  "art_cover": format!("file://{}", root.join("art/gog.jpg").display()) },
+            ]}),
+        );
+        write(
+            root.join("gog_store/installed.json"),
+            json!({ "installed": [{ "appName": "1207658924", "install_path": gog }] }),
+        );
+        write(
+            root.join("sideload_apps/library.json"),
+            json!({ "games": [
+                { "app_name": "side", "title": "Sideloaded", "is_installed": true,
+                  "install": { "install_path": sideloaded, "executable": sideloaded.join("run.exe") } },
+            ]}),
+        );
+
+        let found = Heroic.scan(&[root.join("missing"), root.clone()]).unwrap();
+        let ids: Vec<&str> = found.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, ["heroic:Epic", "heroic:1207658924", "heroic:side"]);
+
+        assert_eq!(found[0].executable, Some(epic.join("Binaries/epic.exe")));
+        assert_eq!(found[0].artwork, None);
+        assert_eq!(found[1].install_dir, gog);
+        assert_eq!(found[1].executable, Some(gog.join("GOG Game.exe")));
+        assert_eq!(found[1].artwork, Some(root.join("art/gog.jpg")));
+        assert_eq!(found[2].executable, Some(sideloaded.join("run.exe")));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{fs, io, iter, path};
+
+use rusqlite::{Connection, OpenFlags};
+use serde_yaml::Value;
+
+use crate::{
+    error::Result,
+    library::{InstalledGame, LibrarySource, env_path, find_executable},
+};
+
+const SOURCE: &str = "lutris";
+const LABEL: &str = "Lutris";
+
+// Artwork Lutris downloads next to its database, named after the game's slug
+const ARTWORK: [(&str, &str); 2] = [("coverart", "jpg"), ("banners", "jpg")];
+
+struct Row {
+    name: String,
+    slug: String,
+    directory: Option<String>,
+    configpath: Option<String>,
+}
+
+fn read_games(database: &path::Path) -> Result<Vec<Row>> {
+    let conn = Connection::open_with_flags(database, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement =
+        conn.prepare("SELECT name, slug, directory, configpath FROM games WHERE installed = 1;")?;
+    let rows = statement.query_map([], |row| {
+        Ok(Row {
+            name: row.get(0)?,
+            slug: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            directory: row.get(2)?,
+            configpath: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<Row>>>()?)
+}
+
+/// The `exe` of a game's YAML configuration, as written.
+fn read_executable(config: &path::Path) -> Result<Option<String>> {
+    let document: Value = serde_yaml::from_slice(&fs::read(config)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(document
+        .get("game")
+        .and_then(|g| g.get("exe"))
+        .and_then(|e| e.as_str())
+        .map(String::from))
+}
+
+/// Installed games of the Lutris data directory `root`. Game configurations are looked up
+/// in `root/games` first, then in every directory of `config_dirs`.
+fn read_library(root: &path::Path, config_dirs: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+    let database = root.join("pga.db");
+    if !database.is_file() {
+        return Ok(vec![]);
+    }
+
+    let mut games = vec![];
+    for row in read_games(&database)? {
+        let directory = row
+            .directory
+            .filter(|d| !d.is_empty())
+            .map(path::PathBuf::from);
+        let config = row.configpath.and_then(|name| {
+            iter::once(root.join("games"))
+                .chain(config_dirs.iter().cloned())
+                .map(|dir| dir.join(format!("{}.yml", name)))
+                .find(|file| file.is_file())
+        });
+        let exe = match config {
+            Some(config) => read_executable(&config).unwrap_or_default(),
+            None => None,
+        };
+        // Relative executables are relative to the game's directory
+        let executable = match (exe, &directory) {
+            (Some(exe), Some(directory)) => Some(directory.join(exe)),
+            (Some(exe), None) => Some(path::PathBuf::from(exe)),
+            (None, Some(directory)) => find_executable(directory, &row.name),
+            (None, None) => None,
+        }
+        .filter(|e| e.is_file());
+        let install_dir = directory
+            .or(executable
+                .as_ref()
+                .and_then(|e| e.parent().map(|p| p.to_path_buf())))
+            .unwrap_or_default();
+        let artwork = ARTWORK
+            .iter()
+            .map(|(dir, ext)| root.join(dir).join(format!("{}.{}", row.slug, ext)))
+            .find(|art| art.is_file());
+
+        games.push(InstalledGame {
+            id: format!("{}:{}", SOURCE, row.slug),
+            source: String::from(LABEL),
+            name: row.name,
+            install_dir,
+            executable,
+            artwork,
+        });
+    }
+    Ok(games)
+}
+
+pub struct Lutris;
+
+impl LibrarySource for Lutris {
+    fn name(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn default_roots(&self) -> Vec<path::PathBuf> {
+        [
+            env_path("XDG_DATA_HOME", &["lutris"]),
+            env_path("HOME", &[".local", "share", "lutris"]),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn scan(&self, roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+        // Older versions of Lutris keep game configurations apart from the database
+        let config_dirs: Vec<path::PathBuf> = [
+            env_path("XDG_CONFIG_HOME", &["lutris", "games"]),
+            env_path("HOME", &[".config", "lutris", "games"]),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let mut games: Vec<InstalledGame> = vec![];
+        for root in roots.iter().filter(|r| r.is_dir()) {
+            for game in read_library(root, &config_dirs)? {
+                if !games.iter().any(|g| g.id == game.id) {
+                    games.push(game);
+                }
+            }
+        }
+        games.sort_by_key(|g| g.name.to_lowercase());
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rusqlite::Connection;
+
+    use super::Lutris;
+    use crate::{
+        library::{LibrarySource, touch},
+        utils,
+    };
+
+    #[test]
+    fn scans_installed_games() {
+        let root = utils::temp_dir();
+        let games = root.join("Games");
+        touch(&games.join("Configured/bin/start.exe"));
+        touch(&games.join("Configured/Configured.exe"));
+        touch(&games.join("Searched/searched.exe"));
+        touch(&games.join("Absolute/absolute.exe"));
+        touch(&root.join("coverart/configured.jpg"));
+        touch(&root.join("banners/searched.jpg"));
+        fs::create_dir_all(root.join("games")).unwrap();
+        fs::write(
+            root.join("games/configured-1.yml"),
+            "game:\n  exe: bin/start.exe\n  prefix: /prefix\nsystem: {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("games/absolute-3.yml"),
+            format!(
+                "game:\n  exe: {}\n",
+                games.join("Absolute/absolute.exe").display()
+            ),
+        )
+        .unwrap();
+
+        let conn = Connection::open(root.join("pga.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE games (name TEXT, slug TEXT, directory TEXT, configpath TEXT,
+            installed INTEGER);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO games VALUES
+            ('Configured', 'configured', ?1, 'configured-1', 1),
+            ('Searched', 'searched', ?2, NULL, 1),
+            ('Absolute', 'absolute', '', 'absolute-3', 1),
+            ('Uninstalled', 'uninstalled', ?2, NULL, 0);",
+            [
+                games.join("Configured").to_str(),
+                games.join("Searched").to_str(),
+            ],
+        )
+        .unwrap();
+        drop(conn);
+
+        let found = Lutris.scan(&[root.join("missing"), root.clone()]).unwrap();
+        let ids: Vec<&str> = found.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["lutris:absolute", "lutris:configured", "lutris:searched"]
+        );
+
+        // Without a directory, the game lives where its executable is
+        assert_eq!(found[0].install_dir, games.join("Absolute"));
+        assert_eq!(
+            found[0].executable,
+            Some(games.join("Absolute/absolute.exe"))
+        );
+        assert_eq!(found[0].artwork, None);
+        assert_eq!(
+            found[1].executable,
+            Some(games.join("Configured/bin/start.exe"))
+        );
+        assert_eq!(found[1].artwork, Some(root.join("coverart/configured.jpg")));
+        assert_eq!(
+            found[2].executable,
+            Some(games.join("Searched/searched.exe"))
+        );
+        assert_eq!(found[2].artwork, Some(root.join("banners/searched.jpg")));
+    }
+}
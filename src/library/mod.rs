@@ -0,0 +1,151 @@
+use std::{cmp::Reverse, env, fs, path};
+
+use crate::error::Result;
+
+pub mod galaxy;
+pub mod heroic;
+pub mod lutris;
+pub mod steam;
+mod vdf;
+
+// Executables that ship next to games but are never the game itself
+const IGNORED_EXECUTABLES: [&str; 10] = [
+    "unins",
+    "setup",
+    "install",
+    "redist",
+    "crash",
+    "report",
+    "dxwebsetup",
+    "prereq",
+    "anticheat",
+    "battleye",
+];
+const IGNORED_DIRECTORIES: [&str; 6] = [
+    "_commonredist",
+    "redist",
+    "directx",
+    "support",
+    "__installer",
+    "easyanticheat",
+];
+const SEARCH_DEPTH: usize = 3;
+
+/// A launcher whose installed games can be imported as custom applications.
+/// Sources are scanned away from the UI thread.
+pub trait LibrarySource: Send {
+    /// Key of the source under `libraries` in GProfiles' settings.
+    fn name(&self) -> &'static str;
+
+    /// Where the launcher keeps its library, used unless the user configured other roots.
+    fn default_roots(&self) -> Vec<path::PathBuf>;
+
+    /// Installed games found under `roots`. Roots that do not exist are skipped.
+    fn scan(&self, roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>>;
+}
+
+pub fn sources() -> Vec<Box<dyn LibrarySource>> {
+    vec![
+        Box::new(steam::Steam),
+        Box::new(heroic::Heroic),
+        Box::new(lutris::Lutris),
+        Box::new(galaxy::GogGalaxy),
+    ]
+}
+
+/// A game found in a launcher's library, not yet known to LGHUB.
+#[derive(Debug)]
+pub struct InstalledGame {
+    pub id: String,
+    pub source: String,
+    pub name: String,
+    pub install_dir: path::PathBuf,
+    pub executable: Option<path::PathBuf>,
+    pub artwork: Option<path::PathBuf>,
+}
+
+impl Clone for InstalledGame {
+    fn clone(&self) -> Self {
+        InstalledGame {
+            id: self.id.clone(),
+            source: self.source.clone(),
+            name: self.name.clone(),
+            install_dir: self.install_dir.clone(),
+            executable: self.executable.clone(),
+            artwork: self.artwork.clone(),
+        }
+    }
+}
+
+/// `children` joined onto the directory held by the environment variable `var`, if it is set.
+fn env_path(var: &str, children: &[&str]) -> Option<path::PathBuf> {
+    let mut path = path::PathBuf::from(env::var_os(var)?);
+    path.extend(children);
+    Some(path)
+}
+
+/// Creates an empty file, and the directories leading to it, for a test.
+#[cfg(test)]
+fn touch(file: &path::Path) {
+    fs::create_dir_all(file.parent().unwrap()).unwrap();
+    fs::write(file, b"").unwrap();
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn collect_executables(dir: &path::Path, depth: usize, found: &mut Vec<(path::PathBuf, usize)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if path.is_dir() {
+            if depth < SEARCH_DEPTH && !IGNORED_DIRECTORIES.contains(&name.as_str()) {
+                collect_executables(&path, depth + 1, found);
+            }
+        } else if name.ends_with(".exe") && !IGNORED_EXECUTABLES.iter().any(|i| name.contains(i)) {
+            found.push((path, depth));
+        }
+    }
+}
+
+/// Best guess at the main executable of a game: one named after the game wins,
+/// otherwise the shallowest and then largest executable in its install directory.
+pub fn find_executable(install_dir: &path::Path, name: &str) -> Option<path::PathBuf> {
+    let mut found = vec![];
+    collect_executables(install_dir, 0, &mut found);
+
+    let names = [
+        normalize(name),
+        install_dir
+            .file_name()
+            .map(|n| normalize(&n.to_string_lossy()))
+            .unwrap_or_default(),
+    ];
+    found
+        .into_iter()
+        .min_by_key(|(path, depth)| {
+            let stem = path
+                .file_stem()
+                .map(|s| normalize(&s.to_string_lossy()))
+                .unwrap_or_default();
+            let rank = if names.contains(&stem) {
+                0
+            } else if names.iter().any(|n| {
+                !stem.is_empty() && !n.is_empty() && (n.contains(&stem) || stem.contains(n))
+            }) {
+                1
+            } else {
+                2
+            };
+            let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            (rank, *depth, Reverse(size))
+        })
+        .map(|(path, _)| path)
+}
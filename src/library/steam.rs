@@ -0,0 +1,310 @@
+use std::{fs, io, path};
+
+use crate::{
+    error::Result,
+    library::{InstalledGame, LibrarySource, find_executable, vdf},
+};
+
+const SOURCE: &str = "steam";
+const LABEL: &str = "Steam";
+
+// Bit of an app manifest's StateFlags set once a game is fully installed
+const STATE_FULLY_INSTALLED: u64 = 4;
+
+/// Every library folder known to the Steam install at `root`, starting with `root` itself.
+/// A list that cannot be read leaves just `root`, whose games are still worth listing.
+pub fn library_folders(root: &path::Path) -> Vec<path::PathBuf> {
+    let mut folders = vec![root.to_path_buf()];
+    let file = root.join("steamapps").join("libraryfolders.vdf");
+    let Ok(document) = fs::read_to_string(&file).and_then(|text| vdf::parse(&text)) else {
+        return folders;
+    };
+    let Some(libraries) = document.get("libraryfolders") else {
+        return folders;
+    };
+    for (key, library) in libraries.entries() {
+        // Older clients map the index straight to a path, newer ones to an object
+        let path = match library.as_str() {
+            Some(path) if key.parse::<u32>().is_ok() => path,
+            Some(_) => continue,
+            None => match library.get_str("path") {
+                Some(path) => path,
+                None => continue,
+            },
+        };
+        let path = path::PathBuf::from(path);
+        if !folders.contains(&path) {
+            folders.push(path);
+        }
+    }
+    folders
+}
+
+/// Reads an `appmanifest_<appid>.acf`, returning `None` for games that are not fully installed.
+pub fn read_manifest(library: &path::Path, file: &path::Path) -> Result<Option<InstalledGame>> {
+    let document = vdf::parse(&fs::read_to_string(file)?)?;
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a Steam app manifest.", file.display()),
+        )
+    };
+    let state = document.get("AppState").ok_or_else(invalid)?;
+    let (Some(id), Some(name), Some(install_dir)) = (
+        state.get_str("appid"),
+        state.get_str("name"),
+        state.get_str("installdir"),
+    ) else {
+        return Err(invalid().into());
+    };
+
+    let flags = state
+        .get_str("StateFlags")
+        .and_then(|f| f.parse::<u64>().ok())
+        .unwrap_or(0);
+    let install_dir = library.join("steamapps").join("common").join(install_dir);
+    if flags & STATE_FULLY_INSTALLED == 0 || !install_dir.is_dir() {
+        return Ok(None);
+    }
+    Ok(Some(InstalledGame {
+        id: format!("{}:{}", SOURCE, id),
+        source: String::from(LABEL),
+        name: name.to_string(),
+        executable: find_executable(&install_dir, name),
+        install_dir,
+        artwork: None,
+    }))
+}
+
+// Steam quotes paths of shortcuts so that they may contain spaces
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"')
+}
+
+/// Reads the non-Steam games of a binary `shortcuts.vdf`.
+pub fn read_shortcuts(file: &path::Path) -> Result<Vec<InstalledGame>> {
+    let document = vdf::parse_binary(&fs::read(file)?)?;
+    let Some(shortcuts) = document.get("shortcuts") else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a Steam shortcuts file.", file.display()),
+        )
+        .into());
+    };
+
+    let mut games = vec![];
+    for (index, shortcut) in shortcuts.entries() {
+        let (Some(name), Some(exe)) = (shortcut.get_str("AppName"), shortcut.get_str("Exe")) else {
+            continue;
+        };
+        let executable = path::PathBuf::from(unquote(exe));
+        let install_dir = match shortcut.get_str("StartDir").map(unquote) {
+            Some(dir) if !dir.is_empty() => path::PathBuf::from(dir),
+            _ => executable
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default(),
+        };
+        // Shortcuts created before Steam assigned ids only have their position
+        let id = shortcut.get_str("appid").unwrap_or(index);
+        games.push(InstalledGame {
+            id: format!("{}-shortcut:{}", SOURCE, id),
+            source: String::from(LABEL),
+            name: name.to_string(),
+            install_dir,
+            executable: executable.is_file().then_some(executable),
+            artwork: shortcut
+                .get_str("icon")
+                .map(|i| path::PathBuf::from(unquote(i)))
+                .filter(|i| i.is_file()),
+        });
+    }
+    Ok(games)
+}
+
+/// Shortcuts of every Steam user of the install at `root`.
+pub fn shortcut_files(root: &path::Path) -> Vec<path::PathBuf> {
+    let Ok(users) = fs::read_dir(root.join("userdata")) else {
+        return vec![];
+    };
+    let mut files: Vec<path::PathBuf> = users
+        .flatten()
+        .map(|u| u.path().join("config").join("shortcuts.vdf"))
+        .filter(|f| f.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+pub struct Steam;
+
+impl LibrarySource for Steam {
+    fn name(&self) -> &'static str {
+        SOURCE
+    }
+
+    fn default_roots(&self) -> Vec<path::PathBuf> {
+        vec![
+            path::PathBuf::from("C:\\Program Files (x86)\\Steam"),
+            path::PathBuf::from("C:\\Program Files\\Steam"),
+        ]
+    }
+
+    fn scan(&self, roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+        scan(roots)
+    }
+}
+
+/// Installed games of every library reachable from `roots`, followed by their non-Steam shortcuts.
+/// Roots that do not exist are skipped, as are library lists, manifests and shortcut files that
+/// cannot be read, so that one broken file does not hide the other games.
+pub fn scan(roots: &[path::PathBuf]) -> Result<Vec<InstalledGame>> {
+    let mut libraries: Vec<path::PathBuf> = vec![];
+    for root in roots.iter().filter(|r| r.is_dir()) {
+        for library in library_folders(root) {
+            if !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+
+    let mut games: Vec<InstalledGame> = vec![];
+    for library in libraries {
+        let Ok(entries) = fs::read_dir(library.join("steamapps")) else {
+            continue;
+        };
+        let mut manifests: Vec<path::PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .map(|n| n.to_string_lossy())
+                    .is_some_and(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"))
+            })
+            .collect();
+        manifests.sort();
+        for manifest in manifests {
+            if let Ok(Some(game)) = read_manifest(&library, &manifest)
+                && !games.iter().any(|g| g.id == game.id)
+            {
+                games.push(game);
+            }
+        }
+    }
+    games.sort_by_key(|g| g.name.to_lowercase());
+
+    let mut shortcuts: Vec<InstalledGame> = vec![];
+    for root in roots.iter().filter(|r| r.is_dir()) {
+        for file in shortcut_files(root) {
+            for game in read_shortcuts(&file).unwrap_or_default() {
+                if !shortcuts.iter().any(|g| g.id == game.id) {
+                    shortcuts.push(game);
+                }
+            }
+        }
+    }
+    shortcuts.sort_by_key(|g| g.name.to_lowercase());
+    games.extend(shortcuts);
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path};
+
+    use super::{library_folders, read_shortcuts, scan};
+    use crate::{library::touch, utils};
+
+    fn root() -> path::PathBuf {
+        path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/library/steam")
+    }
+
+    #[test]
+    fn scans_installed_games_and_shortcuts() {
+        let root = root();
+        let games = scan(&[root.join("missing"), root.clone()]).unwrap();
+        let ids: Vec<&str> = games.iter().map(|g| g.id.as_str()).collect();
+        // Downloading is not fully installed and the manifest of app 30 is broken
+        assert_eq!(ids, ["steam:10", "steam-shortcut:-1234567"]);
+
+        let game = &games[0];
+        let install_dir = root.join("steamapps/common/Half Game");
+        assert_eq!(game.name, "Half Game");
+        assert_eq!(game.executable, Some(install_dir.join("halfgame.exe")));
+        assert_eq!(game.install_dir, install_dir);
+
+        let shortcut = &games[1];
+        assert_eq!(shortcut.name, "Emulator");
+        assert_eq!(shortcut.install_dir, path::PathBuf::from("/nonexistent/"));
+        assert_eq!(shortcut.executable, None);
+        assert_eq!(shortcut.artwork, None);
+    }
+
+    // A binary shortcuts.vdf holding objects of string fields
+    fn shortcuts(entries: &[&[(&str, &str)]]) -> Vec<u8> {
+        let mut data = b"\x00shortcuts\x00".to_vec();
+        for (index, fields) in entries.iter().enumerate() {
+            data.extend(format!("\x00{}\x00", index).bytes());
+            for (key, value) in fields.iter() {
+                data.extend(format!("\x01{}\x00{}\x00", key, value).bytes());
+            }
+            data.push(0x08);
+        }
+        data.extend(b"\x08\x08");
+        data
+    }
+
+    #[test]
+    fn reads_shortcut_icons_that_exist() {
+        let dir = utils::temp_dir();
+        let (executable, icon) = (dir.join("Game/game.exe"), dir.join("Game/icon.png"));
+        touch(&executable);
+        touch(&icon);
+        let quoted = |path: &path::Path| format!("\"{}\"", path.display());
+        let file = dir.join("shortcuts.vdf");
+        fs::write(
+            &file,
+            shortcuts(&[
+                &[
+                    ("AppName", "Game"),
+                    ("Exe", &quoted(&executable)),
+                    ("icon", &icon.to_string_lossy()),
+                ],
+                &[
+                    ("AppName", "Moved"),
+                    ("Exe", &quoted(&executable)),
+                    ("icon", &quoted(&dir.join("missing.png"))),
+                ],
+            ]),
+        )
+        .unwrap();
+
+        let games = read_shortcuts(&file).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].id, "steam-shortcut:0");
+        assert_eq!(games[0].install_dir, dir.join("Game"));
+        assert_eq!(games[0].executable, Some(executable));
+        assert_eq!(games[0].artwork, Some(icon));
+        assert_eq!(games[1].artwork, None);
+    }
+
+    #[test]
+    fn falls_back_to_root_when_library_list_is_broken() {
+        let root = utils::temp_dir();
+        let steamapps = root.join("steamapps");
+        touch(&steamapps.join("common/Game/game.exe"));
+        fs::write(
+            steamapps.join("appmanifest_10.acf"),
+            "\"AppState\" { \"appid\" \"10\" \"name\" \"Game\" \"StateFlags\" \"4\" \"installdir\" \"Game\" }",
+        )
+        .unwrap();
+
+        for list in ["\"libraryfolders\" {", "\"contentstatsid\" \"-1\""] {
+            fs::write(steamapps.join("libraryfolders.vdf"), list).unwrap();
+            assert_eq!(library_folders(&root), std::slice::from_ref(&root));
+            let games = scan(std::slice::from_ref(&root)).unwrap();
+            let ids: Vec<&str> = games.iter().map(|g| g.id.as_str()).collect();
+            assert_eq!(ids, ["steam:10"]);
+        }
+    }
+}
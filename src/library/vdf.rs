@@ -0,0 +1,254 @@
+use std::{io, iter::Peekable, str::Chars};
+
+/// A node of Valve's KeyValues format, as used by `.vdf` and `.acf` files.
+/// Numbers of the binary format are kept as their decimal representation.
+#[derive(Debug)]
+pub enum Node {
+    String(String),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Keys are case-insensitive, Steam is not consistent about them.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|n| n.as_str())
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Node::String(s) => Some(s),
+            Node::Object(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, Node)] {
+        match self {
+            Node::String(_) => &[],
+            Node::Object(entries) => entries,
+        }
+    }
+}
+
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Tokenizer<'_> {
+    fn next(&mut self) -> io::Result<Option<Token>> {
+        loop {
+            let Some(c) = self.chars.next() else {
+                return Ok(None);
+            };
+            match c {
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|s| Some(Token::String(s))),
+                '/' if self.chars.peek() == Some(&'/') => {
+                    self.chars.find(|&c| c == '\n');
+                }
+                // Platform conditionals, e.g. [$WIN32]
+                '[' => {
+                    self.chars.find(|&c| c == ']');
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    let mut token = String::from(c);
+                    while let Some(&c) = self.chars.peek() {
+                        if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                            break;
+                        }
+                        token.push(c);
+                        self.chars.next();
+                    }
+                    return Ok(Some(Token::String(token)));
+                }
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> io::Result<String> {
+        let mut value = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Unterminated string in VDF file.",
+        ))
+    }
+}
+
+fn parse_object(tokens: &mut Tokenizer, nested: bool) -> io::Result<Node> {
+    let mut entries = vec![];
+    loop {
+        let key = match tokens.next()? {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(Node::Object(entries)),
+            None if !nested => return Ok(Node::Object(entries)),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Malformed VDF file.",
+                ));
+            }
+        };
+        let value = match tokens.next()? {
+            Some(Token::String(value)) => Node::String(value),
+            Some(Token::Open) => parse_object(tokens, true)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Missing value for \"{}\" in VDF file.", key),
+                ));
+            }
+        };
+        entries.push((key, value));
+    }
+}
+
+/// Parses a whole text VDF document into an object node.
+pub fn parse(text: &str) -> io::Result<Node> {
+    let mut tokens = Tokenizer {
+        chars: text.chars().peekable(),
+    };
+    parse_object(&mut tokens, false)
+}
+
+// Type tags of the binary format
+const BINARY_OBJECT: u8 = 0x00;
+const BINARY_STRING: u8 = 0x01;
+const BINARY_INT32: u8 = 0x02;
+const BINARY_FLOAT32: u8 = 0x03;
+const BINARY_UINT64: u8 = 0x07;
+const BINARY_END: u8 = 0x08;
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Binary VDF file is truncated.")
+}
+
+fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, count: usize) -> io::Result<&'a [u8]> {
+    let bytes = data.get(*pos..*pos + count).ok_or_else(truncated)?;
+    *pos += count;
+    Ok(bytes)
+}
+
+fn read_cstring(data: &[u8], pos: &mut usize) -> io::Result<String> {
+    let rest = data.get(*pos..).ok_or_else(truncated)?;
+    let end = rest.iter().position(|&b| b == 0).ok_or_else(truncated)?;
+    *pos += end + 1;
+    Ok(String::from_utf8_lossy(&rest[..end]).to_string())
+}
+
+fn parse_binary_object(data: &[u8], pos: &mut usize, nested: bool) -> io::Result<Node> {
+    let mut entries = vec![];
+    loop {
+        let kind = match data.get(*pos) {
+            Some(&kind) => kind,
+            None if !nested => return Ok(Node::Object(entries)),
+            None => return Err(truncated()),
+        };
+        *pos += 1;
+        if kind == BINARY_END {
+            return Ok(Node::Object(entries));
+        }
+        let key = read_cstring(data, pos)?;
+        let value = match kind {
+            BINARY_OBJECT => parse_binary_object(data, pos, true)?,
+            BINARY_STRING => Node::String(read_cstring(data, pos)?),
+            BINARY_INT32 => {
+                let bytes = read_bytes(data, pos, 4)?;
+                Node::String(i32::from_le_bytes(bytes.try_into().unwrap()).to_string()) // safe unwrap
+            }
+            BINARY_FLOAT32 => {
+                let bytes = read_bytes(data, pos, 4)?;
+                Node::String(f32::from_le_bytes(bytes.try_into().unwrap()).to_string()) // safe unwrap
+            }
+            BINARY_UINT64 => {
+                let bytes = read_bytes(data, pos, 8)?;
+                Node::String(u64::from_le_bytes(bytes.try_into().unwrap()).to_string()) // safe unwrap
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown type {:#04x} in binary VDF file.", kind),
+                ));
+            }
+        };
+        entries.push((key, value));
+    }
+}
+
+/// Parses a whole binary VDF document, such as `shortcuts.vdf`, into an object node.
+pub fn parse_binary(data: &[u8]) -> io::Result<Node> {
+    parse_binary_object(data, &mut 0, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_binary};
+
+    const SHORTCUTS: &[u8] =
+        include_bytes!("../../tests/fixtures/library/steam/userdata/1234/config/shortcuts.vdf");
+
+    #[test]
+    fn parses_text() {
+        let document = parse(
+            "// Written by Steam\n\
+             \"AppState\"\n{\n\
+             \t\"appid\"\t\t\"10\"\n\
+             \t\"name\"\t\t\"Half \\\"Game\\\"\"\n\
+             \t\"launch\" [$WIN32] { unquoted value }\n\
+             }\n",
+        )
+        .unwrap();
+        let state = document.get("appstate").unwrap();
+        assert_eq!(state.get_str("APPID"), Some("10"));
+        assert_eq!(state.get_str("name"), Some("Half \"Game\""));
+        assert_eq!(
+            state.get("launch").unwrap().get_str("unquoted"),
+            Some("value")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_text() {
+        assert!(parse("\"AppState\" { \"name\" \"Broken").is_err());
+        assert!(parse("\"AppState\" { \"name\" }").is_err());
+        assert!(parse("\"AppState\" {").is_err());
+    }
+
+    #[test]
+    fn parses_binary() {
+        let document = parse_binary(SHORTCUTS).unwrap();
+        let shortcuts = document.get("shortcuts").unwrap().entries();
+        assert_eq!(shortcuts.len(), 2);
+        let (index, emulator) = &shortcuts[0];
+        assert_eq!(index, "0");
+        assert_eq!(emulator.get_str("appid"), Some("-1234567"));
+        assert_eq!(emulator.get_str("AppName"), Some("Emulator"));
+        assert_eq!(emulator.get("tags").unwrap().get_str("0"), Some("Retro"));
+        assert!(parse_binary(&SHORTCUTS[..SHORTCUTS.len() - 4]).is_err());
+    }
+}
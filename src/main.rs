@@ -3,10 +3,24 @@
 // Hide terminal window
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod artwork;
+mod backup;
+mod catalog;
 mod config;
+mod depots;
+mod duplicates;
+mod error;
+mod library;
+mod merge;
+mod migrations;
+mod pe;
+mod posters;
+mod relocate;
+mod store;
 mod types;
 mod ui;
 mod utils;
+mod watcher;
 
 slint::include_modules!();
 
@@ -14,8 +28,14 @@ fn main() -> Result<(), slint::PlatformError> {
     let ui = App::new()?;
 
     let singleton = ui.global::<Singleton>();
+    let loaded = config::init_config();
+    posters::start(&ui);
     singleton.sync();
+    if let Err(e) = loaded {
+        singleton.show_error(&e);
+    }
     ui.set_callbacks();
+    watcher::watch(&ui);
 
     ui.run()?;
     Ok(())
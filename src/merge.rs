@@ -0,0 +1,259 @@
+use std::{collections::HashSet, error::Error, fmt};
+
+use serde_json::Value;
+
+use crate::types::logitech::{Application, Profile};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Report,
+    Ours,
+    Theirs,
+}
+
+pub trait Mergeable: Clone + Into<Value> {
+    const KIND: &'static str;
+
+    fn key(&self) -> &String;
+    fn label(&self) -> &String;
+}
+
+impl Mergeable for Application {
+    const KIND: &'static str = "application";
+
+    fn key(&self) -> &String {
+        &self.applicationId
+    }
+
+    fn label(&self) -> &String {
+        &self.name
+    }
+}
+
+impl Mergeable for Profile {
+    const KIND: &'static str = "profile";
+
+    fn key(&self) -> &String {
+        &self.id
+    }
+
+    fn label(&self) -> &String {
+        &self.name
+    }
+}
+
+#[derive(Debug)]
+pub struct Conflict {
+    pub kind: &'static str,
+    pub id: String,
+    pub name: String,
+}
+
+impl Clone for Conflict {
+    fn clone(&self) -> Self {
+        Conflict {
+            kind: self.kind,
+            id: self.id.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} \"{}\" ({})", self.kind, self.name, self.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct MergeConflicts(pub Vec<Conflict>);
+
+impl fmt::Display for MergeConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LGHUB changed the same items as GProfiles:")?;
+        for conflict in &self.0 {
+            write!(f, "\n- {}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for MergeConflicts {}
+
+fn same<T: Mergeable>(a: Option<&T>, b: Option<&T>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let (a, b): (Value, Value) = (a.clone().into(), b.clone().into());
+            a == b
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Three-way merge of `ours` and `theirs`, both derived from `base`.
+/// Items are matched by `Mergeable::key`, changes made on only one side are kept.
+/// Items changed differently on both sides are resolved by `resolution`,
+/// `Resolution::Report` keeps theirs and lists them as conflicts.
+pub fn merge<T: Mergeable>(
+    base: &[T],
+    ours: &[T],
+    theirs: &[T],
+    resolution: Resolution,
+) -> (Vec<T>, Vec<Conflict>) {
+    let find = |items: &'_ [T], key: &String| items.iter().find(|i| i.key() == key).cloned();
+
+    let mut keys: Vec<&String> = vec![];
+    let mut seen: HashSet<&String> = HashSet::new();
+    for item in theirs.iter().chain(ours.iter()) {
+        if seen.insert(item.key()) {
+            keys.push(item.key());
+        }
+    }
+
+    let mut merged: Vec<T> = vec![];
+    let mut conflicts: Vec<Conflict> = vec![];
+    for key in keys {
+        let (b, o, t) = (find(base, key), find(ours, key), find(theirs, key));
+        let pick = if same(o.as_ref(), t.as_ref()) || same(b.as_ref(), t.as_ref()) {
+            o
+        } else if same(b.as_ref(), o.as_ref()) {
+            t
+        } else {
+            match resolution {
+                Resolution::Ours => o,
+                Resolution::Theirs => t,
+                Resolution::Report => {
+                    let label = o.as_ref().or(t.as_ref()).unwrap(); // one side always exists
+                    conflicts.push(Conflict {
+                        kind: T::KIND,
+                        id: key.clone(),
+                        name: label.label().clone(),
+                    });
+                    t
+                }
+            }
+        };
+        if let Some(item) = pick {
+            merged.push(item);
+        }
+    }
+    (merged, conflicts)
+}
+
+/// `merge` for profiles, which also keeps a single active profile per application.
+/// LGHUB and GProfiles may each have activated another profile of the same application,
+/// the one active in `ours` wins then.
+pub fn merge_profiles(
+    base: &[Profile],
+    ours: &[Profile],
+    theirs: &[Profile],
+    resolution: Resolution,
+) -> (Vec<Profile>, Vec<Conflict>) {
+    let (mut merged, conflicts) = merge(base, ours, theirs, resolution);
+    let active_in_ours = |profile: &Profile| {
+        ours.iter()
+            .any(|p| p.id == profile.id && p.activeForApplication)
+    };
+    let mut applications: Vec<String> = merged.iter().map(|p| p.applicationId.clone()).collect();
+    applications.sort();
+    applications.dedup();
+    for application in applications {
+        let active: Vec<usize> = (0..merged.len())
+            .filter(|&i| merged[i].applicationId == application && merged[i].activeForApplication)
+            .collect();
+        if active.len() < 2 {
+            continue;
+        }
+        let keep = active
+            .iter()
+            .copied()
+            .find(|&i| active_in_ours(&merged[i]))
+            .unwrap_or(active[0]);
+        for i in active.into_iter().filter(|&i| i != keep) {
+            merged[i].activeForApplication = false;
+        }
+    }
+    (merged, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Resolution, merge, merge_profiles};
+    use crate::types::logitech::Profile;
+
+    fn profile(id: &str, name: &str, active: bool) -> Profile {
+        serde_json::from_value(json!({
+            "activeForApplication": active,
+            "applicationId": "game",
+            "id": id,
+            "name": name,
+            "assignments": [],
+        }))
+        .unwrap()
+    }
+
+    fn active(profiles: &[Profile]) -> Vec<&str> {
+        profiles
+            .iter()
+            .filter(|p| p.activeForApplication)
+            .map(|p| p.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_changes_of_either_side() {
+        let base = vec![profile("a", "A", true), profile("b", "B", false)];
+        let ours = vec![profile("a", "Renamed", true), profile("b", "B", false)];
+        let mut theirs = base.clone();
+        theirs.push(profile("c", "C", false));
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, Resolution::Report);
+        assert!(conflicts.is_empty());
+        let names: Vec<&str> = merged.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Renamed", "B", "C"]);
+    }
+
+    #[test]
+    fn reports_changes_on_both_sides() {
+        let base = vec![profile("a", "A", true)];
+        let ours = vec![profile("a", "Ours", true)];
+        let theirs = vec![profile("a", "Theirs", true)];
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, Resolution::Report);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(merged[0].name, "Theirs");
+        let (merged, _) = merge(&base, &ours, &theirs, Resolution::Ours);
+        assert_eq!(merged[0].name, "Ours");
+    }
+
+    #[test]
+    fn keeps_one_active_profile_per_application() {
+        let base = vec![
+            profile("a", "A", true),
+            profile("b", "B", false),
+            profile("c", "C", false),
+        ];
+        // GProfiles activated b while LGHUB activated c
+        let ours = vec![
+            profile("a", "A", false),
+            profile("b", "B", true),
+            profile("c", "C", false),
+        ];
+        let theirs = vec![
+            profile("a", "A", false),
+            profile("b", "B", false),
+            profile("c", "C", true),
+        ];
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs, Resolution::Report);
+        assert!(conflicts.is_empty());
+        assert_eq!(active(&merged), ["b", "c"]);
+        let (merged, conflicts) = merge_profiles(&base, &ours, &theirs, Resolution::Report);
+        assert!(conflicts.is_empty());
+        assert_eq!(active(&merged), ["b"]);
+        assert_eq!(merged.len(), 3);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{fs, io, path};
+
+use serde_json::{Value, json};
+
+use crate::{error::Result, types::gprofiles::GProfilesData, utils};
+
+pub const SCHEMA_VERSION: u64 = 1;
+
+type Migration = fn(&mut Value);
+
+// MIGRATIONS[n] upgrades a document from version n to n + 1
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1];
+
+// Files written before versioning was introduced
+fn v0_to_v1(document: &mut Value) {
+    if !document.is_object() {
+        *document = json!({});
+    }
+}
+
+fn version_of(document: &Value) -> u64 {
+    document
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+/// Upgrades `document` to `SCHEMA_VERSION` one step at a time.
+pub fn migrate(document: &mut Value) -> Result<()> {
+    let version = version_of(document);
+    if version > SCHEMA_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "settings.json was written by a newer version of GProfiles (schema {}).",
+                version
+            ),
+        )
+        .into());
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(document);
+    }
+    document["schema_version"] = SCHEMA_VERSION.into();
+    Ok(())
+}
+
+/// Reads GProfiles' settings, migrating them in place if they are outdated.
+/// The original file is kept next to it as `settings.v<version>.json`.
+pub fn load(file: &path::Path) -> Result<GProfilesData> {
+    let data = fs::read(file)?;
+    let mut document: Value = serde_json::from_slice(&data)?;
+    let version = version_of(&document);
+    if version != SCHEMA_VERSION {
+        migrate(&mut document)?;
+        fs::write(file.with_extension(format!("v{}.json", version)), &data)?;
+        utils::write_atomic(file, &serde_json::to_vec_pretty(&document)?)?;
+    }
+    Ok(serde_json::from_value(document)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::{Value, json};
+
+    use super::{SCHEMA_VERSION, load, migrate};
+    use crate::utils;
+
+    #[test]
+    fn upgrades_v0_and_keeps_a_copy() {
+        let dir = utils::temp_dir();
+        let file = dir.join("settings.json");
+        let original = serde_json::to_vec(&json!({
+            "keybinds": {
+                "game": [{ "input": "A", "output": "B", "virtual_input": 65, "virtual_output": 66 }],
+            },
+        }))
+        .unwrap();
+        fs::write(&file, &original).unwrap();
+
+        let data = load(&file).unwrap();
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+        assert_eq!(data.keybinds.unwrap()["game"][0].output, "B");
+        assert_eq!(fs::read(dir.join("settings.v0.json")).unwrap(), original);
+        let migrated: Value = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+        assert_eq!(migrated["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn refuses_newer_schemas() {
+        let mut document = json!({ "schema_version": SCHEMA_VERSION + 1 });
+        assert!(migrate(&mut document).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::{fs, io, path};
+
+use image::{DynamicImage, ImageFormat};
+
+use crate::error::Result;
+
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(invalid("Executable is truncated."))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(invalid("Executable is truncated."))
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_offset: u32,
+}
+
+/// The resource tree of a PE (Windows executable) image.
+struct Resources<'a> {
+    data: &'a [u8],
+    sections: Vec<Section>,
+    root: usize,
+}
+
+impl<'a> Resources<'a> {
+    fn parse(data: &'a [u8]) -> io::Result<Self> {
+        if data.get(0..2) != Some(b"MZ") {
+            return Err(invalid("Not a Windows executable."));
+        }
+        let pe = read_u32(data, 0x3c)? as usize;
+        if data.get(pe..pe + 4) != Some(b"PE\0\0") {
+            return Err(invalid("Not a Windows executable."));
+        }
+        let section_count = read_u16(data, pe + 6)? as usize;
+        let optional_size = read_u16(data, pe + 20)? as usize;
+        let optional = pe + 24;
+        let directories = match read_u16(data, optional)? {
+            0x10b => optional + 96,
+            0x20b => optional + 112,
+            _ => return Err(invalid("Unknown executable format.")),
+        };
+        // The resource table is the third data directory
+        let resources_rva = read_u32(data, directories + 2 * 8)?;
+        if resources_rva == 0 {
+            return Err(invalid("Executable has no resources."));
+        }
+
+        let mut sections: Vec<Section> = vec![];
+        let table = optional + optional_size;
+        for i in 0..section_count {
+            let header = table + i * 40;
+            let virtual_size = read_u32(data, header + 8)?;
+            let raw_size = read_u32(data, header + 16)?;
+            sections.push(Section {
+                virtual_address: read_u32(data, header + 12)?,
+                virtual_size: virtual_size.max(raw_size),
+                raw_offset: read_u32(data, header + 20)?,
+            });
+        }
+
+        let mut resources = Resources {
+            data,
+            sections,
+            root: 0,
+        };
+        resources.root = resources.offset_of(resources_rva)?;
+        Ok(resources)
+    }
+
+    fn offset_of(&self, rva: u32) -> io::Result<usize> {
+        // Widened, as sections of a damaged executable may reach past 4 GiB
+        let rva = rva as u64;
+        self.sections
+            .iter()
+            .find(|s| {
+                let start = s.virtual_address as u64;
+                rva >= start && rva < start + s.virtual_size as u64
+            })
+            .map(|s| (rva - s.virtual_address as u64 + s.raw_offset as u64) as usize)
+            .ok_or(invalid("Resource points outside of the executable."))
+    }
+
+    /// Entries of the directory at `offset`, as (id, offset, is_directory).
+    fn entries(&self, offset: usize) -> io::Result<Vec<(u32, usize, bool)>> {
+        let count =
+            read_u16(self.data, offset + 12)? as usize + read_u16(self.data, offset + 14)? as usize;
+        let mut entries = vec![];
+        for i in 0..count {
+            let entry = offset + 16 + i * 8;
+            let id = read_u32(self.data, entry)?;
+            let target = read_u32(self.data, entry + 4)?;
+            entries.push((
+                id,
+                self.root + (target & 0x7fff_ffff) as usize,
+                target & 0x8000_0000 != 0,
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// Data of the first language of every resource with type `kind`, as (id, data).
+    fn find(&self, kind: u32) -> io::Result<Vec<(u32, &'a [u8])>> {
+        let mut found = vec![];
+        for (_, names, _) in self
+            .entries(self.root)?
+            .into_iter()
+            .filter(|e| e.0 == kind && e.2)
+        {
+            for (id, languages, is_directory) in self.entries(names)? {
+                let leaf = if is_directory {
+                    match self.entries(languages)?.first() {
+                        Some((_, leaf, _)) => *leaf,
+                        None => continue,
+                    }
+                } else {
+                    languages
+                };
+                let start = self.offset_of(read_u32(self.data, leaf)?)?;
+                let size = read_u32(self.data, leaf + 4)? as usize;
+                let data = self
+                    .data
+                    .get(start..start + size)
+                    .ok_or(invalid("Executable is truncated."))?;
+                found.push((id, data));
+            }
+        }
+        Ok(found)
+    }
+}
+
+// The largest, then deepest, of icon directory entries, which all start with the same 8 bytes
+fn largest_frame<'e>(entries: impl Iterator<Item = &'e [u8]>) -> io::Result<Option<&'e [u8]>> {
+    let mut best: Option<(u32, u16, &[u8])> = None;
+    for entry in entries {
+        // A size of 0 means 256 pixels
+        let width = if entry[0] == 0 { 256 } else { entry[0] as u32 };
+        let bits = read_u16(entry, 6)?;
+        if best.is_some_and(|(w, b, _)| (w, b) >= (width, bits)) {
+            continue;
+        }
+        best = Some((width, bits, entry));
+    }
+    Ok(best.map(|(_, _, entry)| entry))
+}
+
+// Wraps a frame in a single-image .ico so both PNG and DIB frames decode with alpha
+fn decode_frame(entry: &[u8], frame: &[u8]) -> Result<DynamicImage> {
+    let mut ico: Vec<u8> = vec![0, 0, 1, 0, 1, 0];
+    ico.extend_from_slice(&entry[0..8]);
+    ico.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(frame);
+    Ok(image::load_from_memory_with_format(&ico, ImageFormat::Ico)?)
+}
+
+/// Extracts the largest frame of the main icon of a Windows executable.
+pub fn extract_icon(data: &[u8]) -> Result<DynamicImage> {
+    let resources = Resources::parse(data)?;
+    let groups = resources.find(RT_GROUP_ICON)?;
+    let Some((_, group)) = groups.first() else {
+        return Err(invalid("Executable has no icon.").into());
+    };
+
+    // GRPICONDIR, followed by 14 byte GRPICONDIRENTRY items
+    let count = read_u16(group, 4)? as usize;
+    let entries = (0..count)
+        .map(|i| {
+            group
+                .get(6 + i * 14..6 + (i + 1) * 14)
+                .ok_or(invalid("Icon group is truncated."))
+        })
+        .collect::<io::Result<Vec<&[u8]>>>()?;
+    let Some(entry) = largest_frame(entries.into_iter())? else {
+        return Err(invalid("Executable has no icon.").into());
+    };
+    let id = read_u16(entry, 12)? as u32;
+    let icons = resources.find(RT_ICON)?;
+    let Some((_, icon)) = icons.iter().find(|(i, _)| *i == id) else {
+        return Err(invalid("Icon frame is missing.").into());
+    };
+    decode_frame(entry, icon)
+}
+
+/// Decodes the largest frame of an .ico file. Unlike the image decoder, which favours
+/// colour depth, a bigger frame wins so that posters are as sharp as possible.
+pub fn extract_ico(data: &[u8]) -> Result<DynamicImage> {
+    // ICONDIR, followed by 16 byte ICONDIRENTRY items
+    let count = read_u16(data, 4)? as usize;
+    let entries = (0..count)
+        .map(|i| {
+            data.get(6 + i * 16..6 + (i + 1) * 16)
+                .ok_or(invalid("Icon is truncated."))
+        })
+        .collect::<io::Result<Vec<&[u8]>>>()?;
+    let Some(entry) = largest_frame(entries.into_iter())? else {
+        return Err(invalid("Icon has no frames.").into());
+    };
+    let (size, offset) = (read_u32(entry, 8)? as usize, read_u32(entry, 12)? as usize);
+    let frame = data
+        .get(offset..offset + size)
+        .ok_or(invalid("Icon is truncated."))?;
+    decode_frame(entry, frame)
+}
+
+pub fn icon_from_executable(file: &path::Path) -> Result<DynamicImage> {
+    extract_icon(&fs::read(file)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GenericImageView;
+
+    use super::extract_icon;
+
+    // Both hold one icon group; png_icon.exe has a 16px DIB frame and a 32px PNG frame,
+    // dib_icon.exe only the DIB frame, whose top half is red and bottom half transparent
+    const PNG_ICON: &[u8] = include_bytes!("../tests/fixtures/pe/png_icon.exe");
+    const DIB_ICON: &[u8] = include_bytes!("../tests/fixtures/pe/dib_icon.exe");
+
+    #[test]
+    fn extracts_largest_png_frame() {
+        let icon = extract_icon(PNG_ICON).unwrap();
+        assert_eq!(icon.dimensions(), (32, 32));
+        assert_eq!(icon.get_pixel(0, 0).0, [0, 255, 0, 128]);
+    }
+
+    #[test]
+    fn extracts_dib_frame_with_alpha() {
+        let icon = extract_icon(DIB_ICON).unwrap();
+        assert_eq!(icon.dimensions(), (16, 16));
+        assert_eq!(icon.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(icon.get_pixel(0, 15).0[3], 0);
+    }
+
+    #[test]
+    fn rejects_sections_past_the_address_space() {
+        let mut data = PNG_ICON.to_vec();
+        // The only section and the resource table both start right below 4 GiB
+        let (optional, section) = (0x58, 0x58 + 0xe0);
+        data[section + 12..section + 16].copy_from_slice(&0xffff_fff0u32.to_le_bytes());
+        data[optional + 112..optional + 116].copy_from_slice(&0xffff_fff8u32.to_le_bytes());
+        assert!(extract_icon(&data).is_err());
+        assert!(extract_icon(b"MZ").is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    fs, io, path,
+    sync::{OnceLock, mpsc},
+    thread,
+};
+
+use image::DynamicImage;
+use slint::{ComponentHandle, Image, Rgba8Pixel, SharedPixelBuffer};
+
+use crate::{App, config::get_config, depots::Depot, error::Result, utils};
+
+// Decoded posters kept in memory, the least recently used is dropped first
+const MEMORY_CAPACITY: usize = 128;
+
+static REQUESTS: OnceLock<mpsc::Sender<String>> = OnceLock::new();
+
+thread_local! {
+    // Images cannot leave the UI thread, so neither can the posters made of them
+    static LOADED: RefCell<Lru> = RefCell::new(Lru::new(MEMORY_CAPACITY));
+    static REQUESTED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+    // Not fetched again until LGHUB's settings are reloaded
+    static FAILED: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+struct Lru {
+    capacity: usize,
+    entries: VecDeque<(String, Image)>,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Image> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let image = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(image)
+    }
+
+    fn insert(&mut self, key: String, image: Image) {
+        self.entries.retain(|(k, _)| k != &key);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, image));
+    }
+}
+
+pub enum Poster {
+    Loaded(Image),
+    Loading,
+    Missing,
+}
+
+/// The poster at `url` if it was loaded already. Otherwise it is queued for the worker,
+/// and `App::on_poster_loaded` is called once it is ready.
+pub fn get(url: &str) -> Poster {
+    if let Some(image) = LOADED.with_borrow_mut(|l| l.get(url)) {
+        return Poster::Loaded(image);
+    }
+    if FAILED.with_borrow(|f| f.contains(url)) {
+        return Poster::Missing;
+    }
+    if !REQUESTED.with_borrow_mut(|r| r.insert(url.to_string())) {
+        return Poster::Loading;
+    }
+    match REQUESTS.get() {
+        Some(requests) if requests.send(url.to_string()).is_ok() => Poster::Loading,
+        _ => Poster::Missing,
+    }
+}
+
+/// Keeps what the worker made of `url`, returning the poster as an image of the UI.
+pub fn loaded(url: String, buffer: Option<SharedPixelBuffer<Rgba8Pixel>>) -> Option<Image> {
+    REQUESTED.with_borrow_mut(|r| r.remove(&url));
+    let Some(buffer) = buffer else {
+        FAILED.with_borrow_mut(|f| f.insert(url));
+        return None;
+    };
+    let image = Image::from_rgba8(buffer);
+    LOADED.with_borrow_mut(|l| l.insert(url, image.clone()));
+    Some(image)
+}
+
+/// Lets posters that could not be loaded be fetched again, e.g. once the network is back.
+pub fn retry() {
+    FAILED.with_borrow_mut(|f| f.clear());
+}
+
+/// `url` with its scheme and host replaced by `base`, when one is set.
+pub fn with_base(url: &str, base: Option<&str>) -> String {
+    let Some(base) = base.filter(|b| !b.is_empty()) else {
+        return url.to_string();
+    };
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let path = rest.find('/').map(|i| &rest[i..]).unwrap_or_default();
+    format!("{}{}", base.trim_end_matches('/'), path)
+}
+
+// Name of the cached copy of `url`, a 64-bit FNV-1a hash which, unlike the standard library's
+// hashers, stays the same across builds so the cache survives updates
+fn cache_name(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn fetch(url: &str, cache: Option<&path::Path>, base: Option<&str>) -> Result<DynamicImage> {
+    let cached = cache.map(|c| c.join(cache_name(url)));
+    if let Some(file) = &cached
+        && let Ok(image) = utils::open_image(file)
+    {
+        return Ok(image);
+    }
+
+    let data = reqwest::blocking::get(with_base(url, base))
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map_err(io::Error::other)?;
+    let image = image::load_from_memory(&data)?;
+    if let Some(file) = &cached {
+        // A poster that cannot be cached is fetched again next time
+        let _ = fs::create_dir_all(file.parent().unwrap()) // safe unwrap
+            .and_then(|_| utils::write_atomic(file, &data));
+    }
+    Ok(image)
+}
+
+fn load(url: &str, depot: &mut Option<Depot>) -> Result<SharedPixelBuffer<Rgba8Pixel>> {
+    let (cache, base, depots_root) = {
+        let config = get_config().read().unwrap();
+        (
+            config.get_poster_cache(),
+            config.get_poster_base(),
+            config.get_depots_root(),
+        )
+    };
+    let image = match fetch(url, cache.as_deref(), base.as_deref()) {
+        Ok(image) => image,
+        Err(e) => {
+            // LGHUB ships the posters of the games it knows about
+            if depot.as_ref().is_none_or(|d| d.root() != depots_root) {
+                *depot = Depot::open(&depots_root).ok();
+            }
+            let name = url.rsplit('/').next().unwrap_or_default();
+            let Some(file) = depot.as_ref().and_then(|d| d.image(name)) else {
+                return Err(e);
+            };
+            utils::open_image(file)?
+        }
+    };
+    let buffer = image.to_rgba8();
+    Ok(SharedPixelBuffer::clone_from_slice(
+        buffer.as_raw(),
+        buffer.width(),
+        buffer.height(),
+    ))
+}
+
+/// Starts the worker that fetches posters in the background.
+pub fn start(app: &App) {
+    let (sender, receiver) = mpsc::channel::<String>();
+    if REQUESTS.set(sender).is_err() {
+        return;
+    }
+    let weak = app.as_weak();
+    thread::spawn(move || {
+        // Indexed on the first poster that has to come from it
+        let mut depot: Option<Depot> = None;
+        for url in receiver {
+            let buffer = load(&url, &mut depot).ok();
+            if weak
+                .upgrade_in_event_loop(move |app| app.on_poster_loaded(url, buffer))
+                .is_err()
+            {
+                // Event loop is gone, nobody is waiting for posters
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{Cursor, Read, Write},
+        net::TcpListener,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+    };
+
+    use image::{DynamicImage, ImageFormat, RgbaImage};
+    use slint::SharedPixelBuffer;
+
+    use super::{Poster, REQUESTS, cache_name, fetch, get, loaded, retry};
+    use crate::utils;
+
+    // Serves `poster` at /poster.png and 404 at any other path, returning the base URL and
+    // the number of requests served so far
+    fn serve(poster: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let served = Arc::new(AtomicUsize::new(0));
+        let counter = served.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = if request.starts_with(b"GET /poster.png ") {
+                    ("200 OK", poster.as_slice())
+                } else {
+                    ("404 Not Found", &[][..])
+                };
+                let head = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream
+                    .write_all(head.as_bytes())
+                    .and_then(|_| stream.write_all(body));
+            }
+        });
+        (base, served)
+    }
+
+    fn png() -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::new(2, 3))
+            .write_to(&mut data, ImageFormat::Png)
+            .unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn cache_names_are_stable() {
+        assert_eq!(cache_name(""), "cbf29ce484222325");
+        assert_eq!(cache_name("a"), "af63dc4c8601ec8c");
+        assert_eq!(
+            cache_name("https://example.com/poster.jpg"),
+            cache_name("https://example.com/poster.jpg")
+        );
+    }
+
+    #[test]
+    fn fetches_through_base_and_caches() {
+        let (base, served) = serve(png());
+        let cache = utils::temp_dir().join("posters");
+        let url = "https://example.com/poster.png";
+
+        let image = fetch(url, Some(&cache), Some(&base)).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 3));
+        assert_eq!(fs::read(cache.join(cache_name(url))).unwrap(), png());
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+
+        // Served from the cache from now on
+        fetch(url, Some(&cache), Some(&base)).unwrap();
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+
+        let missing = "https://example.com/missing.png";
+        assert!(fetch(missing, Some(&cache), Some(&base)).is_err());
+        assert!(!cache.join(cache_name(missing)).exists());
+    }
+
+    #[test]
+    fn failed_posters_are_fetched_again_after_retry() {
+        let (base, _) = serve(png());
+        let (sender, requests) = mpsc::channel();
+        REQUESTS.set(sender).unwrap();
+        let url = "https://example.com/poster.png";
+
+        assert!(matches!(get(url), Poster::Loading));
+        assert_eq!(requests.try_recv().unwrap(), url);
+        assert!(loaded(url.to_string(), None).is_none());
+        assert!(matches!(get(url), Poster::Missing));
+        assert!(requests.try_recv().is_err());
+
+        retry();
+        assert!(matches!(get(url), Poster::Loading));
+        assert_eq!(requests.try_recv().unwrap(), url);
+        let image = fetch(url, None, Some(&base)).unwrap().to_rgba8();
+        let buffer =
+            SharedPixelBuffer::clone_from_slice(image.as_raw(), image.width(), image.height());
+        assert!(loaded(url.to_string(), Some(buffer)).is_some());
+        assert!(matches!(get(url), Poster::Loaded(_)));
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, path,
+};
+
+use crate::types::logitech::Application;
+
+const SEARCH_DEPTH: usize = 8;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Field {
+    Executable,
+    Poster,
+}
+
+impl Field {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Field::Executable => "Executable",
+            Field::Poster => "Image",
+        }
+    }
+}
+
+/// A path of an application that no longer exists, and where it may have moved to.
+#[derive(Debug)]
+pub struct Relocation {
+    pub application_id: String,
+    pub name: String,
+    pub field: Field,
+    pub from: String,
+    pub to: Option<String>,
+}
+
+impl Clone for Relocation {
+    fn clone(&self) -> Self {
+        Relocation {
+            application_id: self.application_id.clone(),
+            name: self.name.clone(),
+            field: self.field,
+            from: self.from.clone(),
+            to: self.to.clone(),
+        }
+    }
+}
+
+/// Paths of custom applications that point to missing files.
+pub fn find_broken(applications: &[Application]) -> Vec<Relocation> {
+    let mut broken = vec![];
+    for application in applications.iter().filter(|a| a.isCustom.is_some()) {
+        for (field, value) in [
+            (Field::Executable, &application.applicationPath),
+            (Field::Poster, &application.posterPath),
+        ] {
+            if let Some(value) = value
+                && !value.is_empty()
+                && !path::Path::new(value).exists()
+            {
+                broken.push(Relocation {
+                    application_id: application.applicationId.clone(),
+                    name: application.name.clone(),
+                    field,
+                    from: value.clone(),
+                    to: None,
+                });
+            }
+        }
+    }
+    broken
+}
+
+fn is_separator(c: char) -> bool {
+    c == '\\' || c == '/'
+}
+
+/// Replaces the leading `from` directory of `value` with `to`. Windows paths are case-insensitive
+/// and accept either separator, so both are ignored when matching.
+pub fn rewrite_prefix(value: &str, from: &str, to: &str) -> Option<String> {
+    let from = from.trim_end_matches(is_separator);
+    let to = to.trim_end_matches(is_separator);
+    if from.is_empty() {
+        return None;
+    }
+    let mut rest = value.char_indices();
+    for expected in from.chars() {
+        let (_, actual) = rest.next()?;
+        let matches = (is_separator(expected) && is_separator(actual))
+            || expected.to_lowercase().eq(actual.to_lowercase());
+        if !matches {
+            return None;
+        }
+    }
+    // The prefix must end on a whole directory name
+    let rest = match rest.next() {
+        None => "",
+        Some((i, c)) if is_separator(c) => &value[i..],
+        Some(_) => return None,
+    };
+    Some(format!("{}{}", to, rest))
+}
+
+fn collect_files(
+    dir: &path::Path,
+    depth: usize,
+    wanted: &HashSet<String>,
+    found: &mut HashMap<String, path::PathBuf>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < SEARCH_DEPTH {
+                collect_files(&path, depth + 1, wanted, found);
+            }
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if wanted.contains(&name) && !found.contains_key(&name) {
+            found.insert(name, path);
+        }
+    }
+}
+
+fn file_name(value: &str) -> String {
+    value
+        .rsplit(is_separator)
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Fills in `to` for every relocation that can be fixed, trying the prefix rule first
+/// and then a search for a file with the same name under `roots`.
+pub fn suggest(broken: &mut [Relocation], prefix: Option<(&str, &str)>, roots: &[path::PathBuf]) {
+    for relocation in broken.iter_mut() {
+        relocation.to = prefix
+            .and_then(|(from, to)| rewrite_prefix(&relocation.from, from, to))
+            .filter(|to| path::Path::new(to).exists());
+    }
+
+    let wanted: HashSet<String> = broken
+        .iter()
+        .filter(|r| r.to.is_none())
+        .map(|r| file_name(&r.from))
+        .filter(|name| !name.is_empty())
+        .collect();
+    if wanted.is_empty() {
+        return;
+    }
+    let mut found: HashMap<String, path::PathBuf> = HashMap::new();
+    for root in roots {
+        collect_files(root, 0, &wanted, &mut found);
+    }
+    for relocation in broken.iter_mut().filter(|r| r.to.is_none()) {
+        relocation.to = found
+            .get(&file_name(&relocation.from))
+            .map(|p| p.to_string_lossy().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{Field, Relocation, rewrite_prefix, suggest};
+    use crate::utils;
+
+    fn relocation(from: &str) -> Relocation {
+        Relocation {
+            application_id: String::from("game"),
+            name: String::from("Game"),
+            field: Field::Executable,
+            from: from.to_string(),
+            to: None,
+        }
+    }
+
+    #[test]
+    fn rewrites_whole_directories() {
+        assert_eq!(
+            rewrite_prefix("D:\\Games\\Game\\game.exe", "d:/games/", "E:\\Games"),
+            Some(String::from("E:\\Games\\Game\\game.exe"))
+        );
+        assert_eq!(
+            rewrite_prefix("D:\\Games", "D:\\Games", "E:\\Games\\"),
+            Some(String::from("E:\\Games"))
+        );
+        assert_eq!(
+            rewrite_prefix("D:\\GamesOld\\game.exe", "D:\\Games", "E:\\"),
+            None
+        );
+        assert_eq!(rewrite_prefix("D:\\game.exe", "D:\\Games", "E:\\"), None);
+        assert_eq!(rewrite_prefix("D:\\game.exe", "\\", "E:\\"), None);
+    }
+
+    #[test]
+    fn suggests_prefix_then_search() {
+        let dir = utils::temp_dir();
+        let (old, new, search) = (dir.join("old"), dir.join("new"), dir.join("search"));
+        fs::create_dir_all(new.join("Game")).unwrap();
+        fs::write(new.join("Game").join("game.exe"), b"").unwrap();
+        fs::create_dir_all(search.join("deep").join("er")).unwrap();
+        fs::write(search.join("deep").join("er").join("Other.exe"), b"").unwrap();
+        let (old, new) = (old.to_string_lossy(), new.to_string_lossy());
+
+        let mut broken = vec![
+            relocation(&format!("{}/Game/game.exe", old)),
+            // Moved elsewhere, found by its name regardless of case
+            relocation(&format!("{}/Other/other.exe", old)),
+            relocation(&format!("{}/Gone/gone.exe", old)),
+        ];
+        suggest(
+            &mut broken,
+            Some((&old, &new)),
+            &[dir.join("missing"), search.clone()],
+        );
+        assert_eq!(
+            broken[0].to.as_deref(),
+            Some(format!("{}/Game/game.exe", new).as_str())
+        );
+        assert_eq!(
+            broken[1].to.as_deref(),
+            Some(search.join("deep/er/Other.exe").to_string_lossy().as_ref())
+        );
+        assert_eq!(broken[2].to, None);
+    }
+}
@@ -0,0 +1,254 @@
+use std::{fmt, fs, io, path, sync::Mutex};
+
+use rusqlite::{Connection, MAIN_DB, TransactionBehavior, backup::Progress};
+use serde_json::Value;
+
+use crate::{error::Result, types::logitech::LogitechData, utils};
+
+// Names of the copies that stores keep in a backup
+const SQLITE_SNAPSHOT: &str = "settings.db";
+const JSON_SNAPSHOT: &str = "lghub.json";
+
+/// The store of the LGHUB install at `location`. Releases of LGHUB from before settings.db
+/// kept the same document in a plain settings.json.
+pub fn open(location: &path::Path) -> Box<dyn SettingsStore> {
+    let database = location.join("settings.db");
+    let document = location.join("settings.json");
+    if !database.exists() && document.exists() {
+        Box::new(JsonStore::new(document))
+    } else {
+        Box::new(SqliteStore::new(database))
+    }
+}
+
+/// Checks that `location` holds readable LGHUB settings.
+pub fn validate_location(location: &path::Path) -> Result<()> {
+    let store = open(location);
+    if !store.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No LGHUB settings found in {}.", location.display()),
+        )
+        .into());
+    }
+    let document: Value = serde_json::from_slice(&store.read()?)?;
+    if document.get("applications").is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not hold LGHUB settings.", location.display()),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub trait SettingsStore: fmt::Debug + Send + Sync {
+    /// Raw LGHUB settings document.
+    fn read(&self) -> Result<Vec<u8>>;
+
+    /// Replaces the document with the output of `pred`, which receives the current one.
+    /// Backends must not let other writers in between reading and writing, unless the file
+    /// format offers no way to do so, see `JsonStore`.
+    fn update(&self, pred: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>) -> Result<()>;
+
+    fn exists(&self) -> bool {
+        true
+    }
+
+    /// Files backing this store, if any.
+    fn files(&self) -> Vec<path::PathBuf> {
+        vec![]
+    }
+
+    /// Writes a consistent copy of the store into the directory `dir`.
+    fn snapshot(&self, _dir: &path::Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Replaces the store with the copy `snapshot` wrote into `dir`, if there is one.
+    fn restore(&self, _dir: &path::Path) -> Result<()> {
+        Ok(())
+    }
+
+    fn load(&self) -> Result<LogitechData> {
+        Ok(serde_json::from_slice(&self.read()?)?)
+    }
+
+    fn save(&self, data: &LogitechData) -> Result<()> {
+        let value: Value = data.clone().into();
+        let new = serde_json::to_vec(&value)?;
+        self.update(&mut |_| Ok(new.clone()))
+    }
+
+    fn fingerprint(&self) -> Result<u64> {
+        Ok(utils::fingerprint(&self.read()?))
+    }
+}
+
+#[derive(Debug)]
+pub struct SqliteStore {
+    path: path::PathBuf,
+}
+
+impl SqliteStore {
+    pub fn new(path: path::PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SettingsStore for SqliteStore {
+    fn read(&self) -> Result<Vec<u8>> {
+        let conn = Connection::open(&self.path)?;
+        Ok(conn.query_row("SELECT file FROM data;", [], |row| row.get(0))?)
+    }
+
+    fn update(&self, pred: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>) -> Result<()> {
+        let mut conn = Connection::open(&self.path)?;
+        // Takes the write lock before reading, so that LGHUB cannot write in between
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let current: Vec<u8> = tx.query_row("SELECT file FROM data;", [], |row| row.get(0))?;
+        // Dropping the transaction on error rolls it back
+        let new = pred(current)?;
+        tx.execute("UPDATE data SET file = ?1;", [new])?;
+        Ok(tx.commit()?)
+    }
+
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn files(&self) -> Vec<path::PathBuf> {
+        let mut wal = self.path.clone().into_os_string();
+        wal.push("-wal");
+        vec![self.path.clone(), path::PathBuf::from(wal)]
+    }
+
+    // Both go through SQLite, copying the files could miss what is still in the write-ahead log
+    fn snapshot(&self, dir: &path::Path) -> Result<()> {
+        let conn = Connection::open(&self.path)?;
+        Ok(conn.backup(MAIN_DB, dir.join(SQLITE_SNAPSHOT), None)?)
+    }
+
+    fn restore(&self, dir: &path::Path) -> Result<()> {
+        let file = dir.join(SQLITE_SNAPSHOT);
+        if !file.exists() {
+            return Ok(());
+        }
+        let mut conn = Connection::open(&self.path)?;
+        Ok(conn.restore(MAIN_DB, file, None::<fn(Progress)>)?)
+    }
+}
+
+/// A plain settings.json. LGHUB does not lock it while writing, so unlike `SqliteStore`,
+/// a write of LGHUB's between reading and replacing the document is lost; the document
+/// is replaced atomically, so it is never left half written.
+#[derive(Debug)]
+pub struct JsonStore {
+    path: path::PathBuf,
+}
+
+impl JsonStore {
+    pub fn new(path: path::PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SettingsStore for JsonStore {
+    fn read(&self) -> Result<Vec<u8>> {
+        Ok(fs::read(&self.path)?)
+    }
+
+    fn update(&self, pred: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>) -> Result<()> {
+        let new = pred(self.read()?)?;
+        Ok(utils::write_atomic(&self.path, &new)?)
+    }
+
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn files(&self) -> Vec<path::PathBuf> {
+        vec![self.path.clone()]
+    }
+
+    fn snapshot(&self, dir: &path::Path) -> Result<()> {
+        Ok(utils::write_atomic(
+            &dir.join(JSON_SNAPSHOT),
+            &self.read()?,
+        )?)
+    }
+
+    fn restore(&self, dir: &path::Path) -> Result<()> {
+        let file = dir.join(JSON_SNAPSHOT);
+        if !file.exists() {
+            return Ok(());
+        }
+        // Parsed first, so that a damaged copy is refused rather than restored
+        self.save(&JsonStore::new(file).load()?)
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryStore {
+    data: Mutex<Vec<u8>>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new(br#"{"applications":{"applications":[]},"profiles":{"profiles":[]}}"#.to_vec())
+    }
+}
+
+impl MemoryStore {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: Mutex::new(data),
+        }
+    }
+}
+
+impl SettingsStore for MemoryStore {
+    fn read(&self) -> Result<Vec<u8>> {
+        Ok(self.data.lock().unwrap().clone())
+    }
+
+    fn update(&self, pred: &mut dyn FnMut(Vec<u8>) -> Result<Vec<u8>>) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        *data = pred(data.clone())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use super::{SettingsStore, SqliteStore};
+    use crate::utils;
+
+    #[test]
+    fn sqlite_snapshot_includes_write_ahead_log() {
+        let dir = utils::temp_dir();
+        let file = dir.join("settings.db");
+        // Kept open, so that the write stays in the write-ahead log
+        let conn = Connection::open(&file).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.execute_batch("CREATE TABLE data (file BLOB); INSERT INTO data VALUES ('{}');")
+            .unwrap();
+        conn.execute("UPDATE data SET file = ?1;", [b"before".to_vec()])
+            .unwrap();
+
+        assert!(dir.join("settings.db-wal").exists());
+
+        let store = SqliteStore::new(file);
+        let backup = dir.join("backup");
+        std::fs::create_dir(&backup).unwrap();
+        store.snapshot(&backup).unwrap();
+        assert!(!backup.join("settings.db-wal").exists());
+
+        store.update(&mut |_| Ok(b"after".to_vec())).unwrap();
+        store.restore(&backup).unwrap();
+        assert_eq!(store.read().unwrap(), b"before");
+        drop(conn);
+    }
+}
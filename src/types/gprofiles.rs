@@ -2,12 +2,41 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct GProfilesData {
+    #[serde(default)]
+    pub schema_version: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lghub: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keybinds: Option<HashMap<String, Vec<Keybind>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backups: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<Target>>,
+    // Library roots per launcher, overriding where they are looked for by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub libraries: Option<HashMap<String, Vec<String>>>,
+    // Where posters of installed applications are fetched from, in place of Logitech's servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub posters: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depots: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Target {
+    pub name: String,
+    pub path: String,
+}
+
+impl Clone for Target {
+    fn clone(&self) -> Self {
+        Target {
+            name: self.name.clone(),
+            path: self.path.clone(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -24,7 +53,16 @@ impl Clone for Keybind {
             input: self.input.clone(),
             output: self.output.clone(),
             virtual_input: self.virtual_input,
-            virtual_output: self.virtual_input,
+            virtual_output: self.virtual_output,
         }
     }
 }
+
+impl PartialEq for Keybind {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+            && self.output == other.output
+            && self.virtual_input == other.virtual_input
+            && self.virtual_output == other.virtual_output
+    }
+}
@@ -1,35 +1,122 @@
 #![allow(non_snake_case)]
 
-use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
 
-use crate::utils::APPLICATION_NAME_DESKTOP;
+use crate::utils::PROFILE_NAME_DEFAULT;
+
+fn with_extra(mut data: Value, extra: Map<String, Value>) -> Value {
+    if let Value::Object(map) = &mut data {
+        for (key, value) in extra {
+            map.entry(key).or_insert(value);
+        }
+    }
+    data
+}
+
+// Known fields written as null are not captured by `extra`, keep them there so they stay null
+fn deserialize_applications<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Application>, D::Error> {
+    let entries = Vec::<Map<String, Value>>::deserialize(deserializer)?;
+    let mut applications = vec![];
+    for fields in entries {
+        let nulls: Vec<String> = fields
+            .iter()
+            .filter(|(_, value)| value.is_null())
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut application: Application =
+            serde_json::from_value(Value::Object(fields)).map_err(de::Error::custom)?;
+        for key in nulls {
+            application.extra.entry(key).or_insert(Value::Null);
+        }
+        applications.push(application);
+    }
+    Ok(applications)
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct LogitechData {
     pub applications: InnerApplications,
     pub profiles: InnerProfiles,
+
+    // Everything else G HUB keeps in its settings (cards, devices, lighting, ...)
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Clone for LogitechData {
+    fn clone(&self) -> Self {
+        LogitechData {
+            applications: self.applications.clone(),
+            profiles: self.profiles.clone(),
+            extra: self.extra.clone(),
+        }
+    }
+}
+
+impl From<LogitechData> for Value {
+    fn from(data: LogitechData) -> Self {
+        let applications: Value = data.applications.into();
+        let profiles: Value = data.profiles.into();
+        with_extra(
+            json!({
+                "applications": applications,
+                "profiles": profiles,
+            }),
+            data.extra,
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InnerApplications {
+    #[serde(deserialize_with = "deserialize_applications")]
     pub applications: Vec<Application>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Clone for InnerApplications {
+    fn clone(&self) -> Self {
+        InnerApplications {
+            applications: self.applications.clone(),
+            extra: self.extra.clone(),
+        }
+    }
 }
 
 impl Into<Value> for InnerApplications {
     fn into(self) -> Value {
-        json!(self.applications)
+        let applications: Vec<Value> = self.applications.into_iter().map(|a| a.into()).collect();
+        with_extra(json!({ "applications": applications }), self.extra)
     }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct InnerProfiles {
     pub profiles: Vec<Profile>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Clone for InnerProfiles {
+    fn clone(&self) -> Self {
+        InnerProfiles {
+            profiles: self.profiles.clone(),
+            extra: self.extra.clone(),
+        }
+    }
 }
 
 impl Into<Value> for InnerProfiles {
     fn into(self) -> Value {
-        json!(self.profiles)
+        let profiles: Vec<Value> = self.profiles.into_iter().map(|p| p.into()).collect();
+        with_extra(json!({ "profiles": profiles }), self.extra)
     }
 }
 
@@ -63,6 +150,28 @@ pub struct Application {
     pub posterTitlePosition: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub posterUrl: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Application {
+    pub fn custom(name: String, applicationPath: String, posterPath: String) -> Self {
+        Application {
+            name,
+            applicationId: Uuid::new_v4().to_string(),
+            applicationPath: Some(applicationPath),
+            databaseId: None,
+            version: None,
+            isCustom: Some(true),
+            posterPath: Some(posterPath),
+            applicationFolder: None,
+            isInstalled: None,
+            posterTitlePosition: None,
+            posterUrl: None,
+            extra: Map::new(),
+        }
+    }
 }
 
 impl Clone for Application {
@@ -79,6 +188,7 @@ impl Clone for Application {
             posterTitlePosition: self.posterTitlePosition.clone(),
             posterUrl: self.posterUrl.clone(),
             version: self.version.clone(),
+            extra: self.extra.clone(),
         }
     }
 }
@@ -100,19 +210,29 @@ impl Into<Value> for Application {
         }
         if let Some(isCustom) = self.isCustom {
             data["isCustom"] = isCustom.into();
-            data["posterPath"] = self.posterPath.unwrap().into();
+        }
+        if let Some(posterPath) = self.posterPath {
+            data["posterPath"] = posterPath.into();
         }
         if let Some(isInstalled) = self.isInstalled {
             data["isInstalled"] = isInstalled.into();
-            data["applicationFolder"] = self.applicationFolder.unwrap().into();
-            data["posterTitlePosition"] = self.posterTitlePosition.unwrap().into();
-            data["posterUrl"] = self.posterUrl.unwrap().into();
         }
-        if self.name == APPLICATION_NAME_DESKTOP || self.isInstalled.is_some() {
-            data["databaseId"] = self.databaseId.unwrap().into();
-            data["version"] = self.version.unwrap().into();
+        if let Some(applicationFolder) = self.applicationFolder {
+            data["applicationFolder"] = applicationFolder.into();
+        }
+        if let Some(posterTitlePosition) = self.posterTitlePosition {
+            data["posterTitlePosition"] = posterTitlePosition.into();
+        }
+        if let Some(posterUrl) = self.posterUrl {
+            data["posterUrl"] = posterUrl.into();
+        }
+        if let Some(databaseId) = self.databaseId {
+            data["databaseId"] = databaseId.into();
         }
-        data
+        if let Some(version) = self.version {
+            data["version"] = version.into();
+        }
+        with_extra(data, self.extra)
     }
 }
 
@@ -174,6 +294,22 @@ pub struct Profile {
     pub id: String,
     pub name: String,
     pub assignments: Vec<Assignment>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Profile {
+    pub fn default_for(applicationId: String) -> Self {
+        Profile {
+            activeForApplication: true,
+            applicationId,
+            id: Uuid::new_v4().to_string(),
+            name: String::from(PROFILE_NAME_DEFAULT),
+            assignments: vec![],
+            extra: Map::new(),
+        }
+    }
 }
 
 impl Clone for Profile {
@@ -184,6 +320,7 @@ impl Clone for Profile {
             id: self.id.clone(),
             name: self.name.clone(),
             assignments: self.assignments.clone(),
+            extra: self.extra.clone(),
         }
     }
 }
@@ -196,13 +333,17 @@ impl PartialEq for Profile {
 
 impl Into<Value> for Profile {
     fn into(self) -> Value {
-        json!({
-            "activeForApplication": self.activeForApplication,
-            "applicationId": self.applicationId,
-            "id": self.id,
-            "name": self.name,
-            "assignments": self.assignments,
-        })
+        let assignments: Vec<Value> = self.assignments.into_iter().map(|a| a.into()).collect();
+        with_extra(
+            json!({
+                "activeForApplication": self.activeForApplication,
+                "applicationId": self.applicationId,
+                "id": self.id,
+                "name": self.name,
+                "assignments": assignments,
+            }),
+            self.extra,
+        )
     }
 }
 
@@ -210,6 +351,9 @@ impl Into<Value> for Profile {
 pub struct Assignment {
     cardId: String,
     slotId: String,
+
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 impl Clone for Assignment {
@@ -217,15 +361,73 @@ impl Clone for Assignment {
         Assignment {
             cardId: self.cardId.clone(),
             slotId: self.slotId.clone(),
+            extra: self.extra.clone(),
         }
     }
 }
 
 impl Into<Value> for Assignment {
     fn into(self) -> Value {
-        json!({
-            "cardId": self.cardId,
-            "slotId": self.slotId,
-        })
+        with_extra(
+            json!({
+                "cardId": self.cardId,
+                "slotId": self.slotId,
+            }),
+            self.extra,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::LogitechData;
+
+    #[test]
+    fn round_trip_keeps_unknown_fields() {
+        let fixture = json!({
+            "applications": {
+                "applications": [
+                    {
+                        "name": "APPLICATION_NAME_DESKTOP",
+                        "applicationId": "desktop",
+                        "categoryColors": [{ "hex": "#ffffff", "tag": "TAG" }],
+                    },
+                    {
+                        "name": "Game",
+                        "applicationId": "game",
+                        "applicationPath": "C:\\Games\\game.exe",
+                        "isCustom": true,
+                        "posterPath": null,
+                        "commands": [{ "cardId": "card", "category": "c", "name": "n" }],
+                        "unknownNull": null,
+                    },
+                ],
+                "version": 7,
+            },
+            "profiles": {
+                "profiles": [
+                    {
+                        "activeForApplication": true,
+                        "applicationId": "game",
+                        "id": "profile",
+                        "name": "PROFILE_NAME_DEFAULT",
+                        "syncLighting": false,
+                        "assignments": [
+                            { "cardId": "card", "slotId": "slot", "layerId": "shift" },
+                        ],
+                    },
+                ],
+                "active": "profile",
+            },
+            "cards": { "cards": [{ "id": "card" }] },
+            "devices": { "g502": { "dpi": [800, 1600] } },
+            "lighting": null,
+        });
+
+        let data: LogitechData = serde_json::from_value(fixture.clone()).unwrap();
+        let output: Value = data.into();
+        assert_eq!(output, fixture);
     }
 }
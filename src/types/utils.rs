@@ -1,9 +1,16 @@
-use std::{io, path};
+use std::path;
 
 use slint::{Image, Model, ModelRc, SharedString, VecModel};
 
 use crate::{
-    ApplicationModel, ApplicationType, KeybindModel, ProfileModel,
+    ApplicationModel, ApplicationType, BackupModel, CatalogGameModel, DuplicateGroupModel,
+    KeybindModel, LibraryGameModel, ProfileModel, RelocationModel,
+    backup::Backup,
+    catalog::CatalogGame,
+    duplicates::DuplicateGroup,
+    library::InstalledGame,
+    posters::{self, Poster},
+    relocate::Relocation,
     types::{
         gprofiles::Keybind,
         logitech::{Application, Profile},
@@ -11,19 +18,6 @@ use crate::{
     utils::{Cast, DESKTOP_ICON, PROFILE_NAME_DEFAULT},
 };
 
-fn poster_from_name(name: &str) -> io::Result<Image> {
-    let depots = path::Path::new("C:\\ProgramData\\LGHUB\\depots");
-    let parent = depots.read_dir()?.last().unwrap()?;
-    Ok(Image::using(
-        parent.path().join("core_apps\\images").join(name),
-    ))
-}
-
-fn poster_from_url(url: &String) -> reqwest::Result<Image> {
-    let data = reqwest::blocking::get(url)?.bytes()?;
-    Ok(Image::using(image::load_from_memory(&data).unwrap()))
-}
-
 pub trait Component<T> {
     fn as_component(&self) -> T;
 }
@@ -40,7 +34,11 @@ impl Component<ApplicationModel> for Application {
                 (Some(poster_path), _) => {
                     let as_path = path::Path::new(&poster_path);
                     (
-                        ApplicationType::Installed,
+                        if self.isCustom.is_some() {
+                            ApplicationType::Custom
+                        } else {
+                            ApplicationType::Installed
+                        },
                         SharedString::from(poster_path),
                         as_path.exists(),
                         Image::using(as_path),
@@ -48,21 +46,28 @@ impl Component<ApplicationModel> for Application {
                     )
                 }
                 (None, Some(poster_url)) => {
-                    let poster_name = poster_url.split("/").last().unwrap();
-                    let as_path = match (poster_from_url(poster_url), poster_from_name(poster_name))
-                    {
-                        (Ok(data), _) => Some(data),
-                        (Err(_), Ok(data)) => Some(data),
-                        (Err(_), Err(_)) => None,
+                    // Posters are fetched in the background, the name is shown until then
+                    let (has_icon, icon) = match posters::get(poster_url) {
+                        Poster::Loaded(image) => (true, image),
+                        Poster::Loading => (false, Image::default()),
+                        Poster::Missing => (false, Image::using(path::PathBuf::new())),
                     };
                     (
                         ApplicationType::Installed,
                         SharedString::from(poster_url),
-                        as_path.is_some(),
-                        as_path.unwrap_or(Image::using(path::PathBuf::new())),
+                        has_icon,
+                        icon,
                         SharedString::new(),
                     )
                 }
+                // Added from LGHUB's database without a poster, the name is shown instead
+                (None, None) if self.isInstalled.is_some() => (
+                    ApplicationType::Installed,
+                    SharedString::new(),
+                    false,
+                    Image::using(path::PathBuf::new()),
+                    SharedString::new(),
+                ),
                 _ => {
                     let icon = Image::using(image::load_from_memory(DESKTOP_ICON).unwrap());
                     (
@@ -168,3 +173,120 @@ impl Component<Vec<KeybindModel>> for Vec<Keybind> {
             .collect()
     }
 }
+
+impl Component<BackupModel> for Backup {
+    fn as_component(&self) -> BackupModel {
+        BackupModel {
+            name: SharedString::from(self.name()),
+            path: SharedString::from(self.path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+impl Component<ModelRc<BackupModel>> for Vec<Backup> {
+    fn as_component(&self) -> ModelRc<BackupModel> {
+        ModelRc::new(
+            self.iter()
+                .map(|backup| backup.as_component())
+                .collect::<VecModel<BackupModel>>(),
+        )
+    }
+}
+
+impl Component<LibraryGameModel> for InstalledGame {
+    fn as_component(&self) -> LibraryGameModel {
+        LibraryGameModel {
+            name: SharedString::from(&self.name),
+            source: SharedString::from(&self.source),
+            install_dir: SharedString::from(self.install_dir.to_string_lossy().to_string()),
+            executable: SharedString::from(
+                self.executable
+                    .as_ref()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            artwork: SharedString::from(
+                self.artwork
+                    .as_ref()
+                    .map(|a| a.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            selected: false,
+        }
+    }
+}
+
+impl Component<ModelRc<LibraryGameModel>> for Vec<InstalledGame> {
+    fn as_component(&self) -> ModelRc<LibraryGameModel> {
+        ModelRc::new(
+            self.iter()
+                .map(|game| game.as_component())
+                .collect::<VecModel<LibraryGameModel>>(),
+        )
+    }
+}
+
+impl Component<RelocationModel> for Relocation {
+    fn as_component(&self) -> RelocationModel {
+        RelocationModel {
+            id: SharedString::from(&self.application_id),
+            name: SharedString::from(&self.name),
+            field: SharedString::from(self.field.label()),
+            from: SharedString::from(&self.from),
+            to: SharedString::from(self.to.as_ref().unwrap_or(&String::new())),
+        }
+    }
+}
+
+impl Component<ModelRc<RelocationModel>> for Vec<Relocation> {
+    fn as_component(&self) -> ModelRc<RelocationModel> {
+        ModelRc::new(
+            self.iter()
+                .map(|relocation| relocation.as_component())
+                .collect::<VecModel<RelocationModel>>(),
+        )
+    }
+}
+
+impl Component<DuplicateGroupModel> for DuplicateGroup {
+    fn as_component(&self) -> DuplicateGroupModel {
+        DuplicateGroupModel {
+            executable: SharedString::from(&self.executable),
+            names: SharedString::from(
+                self.applications
+                    .iter()
+                    .map(|a| a.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+            ),
+        }
+    }
+}
+
+impl Component<ModelRc<DuplicateGroupModel>> for Vec<DuplicateGroup> {
+    fn as_component(&self) -> ModelRc<DuplicateGroupModel> {
+        ModelRc::new(
+            self.iter()
+                .map(|group| group.as_component())
+                .collect::<VecModel<DuplicateGroupModel>>(),
+        )
+    }
+}
+
+impl Component<CatalogGameModel> for CatalogGame {
+    fn as_component(&self) -> CatalogGameModel {
+        CatalogGameModel {
+            id: SharedString::from(&self.database_id),
+            name: SharedString::from(&self.name),
+            detection: SharedString::from(
+                self.detection
+                    .iter()
+                    .map(|d| d.label())
+                    .collect::<Vec<String>>()
+                    .join("; "),
+            ),
+            poster: SharedString::from(self.poster_url.as_ref().unwrap_or(&String::new())),
+            added: false,
+        }
+    }
+}
@@ -1,21 +1,127 @@
-use std::{cmp::Ordering, path};
+use std::{cell::RefCell, cmp::Ordering, fs, path, sync::RwLockWriteGuard, thread};
 
-use image::{ImageReader, imageops::FilterType::CatmullRom};
-use slint::{ComponentHandle, Model, ModelExt, ModelRc, SharedString, VecModel};
+use image::DynamicImage;
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use slint::{
+    ComponentHandle, Image, Model, ModelExt, ModelRc, Rgba8Pixel, SharedPixelBuffer, SharedString,
+    VecModel,
+};
 use uuid::Uuid;
 
 use crate::{
-    App, ApplicationModel, ProfileModel, Singleton,
-    config::get_config,
-    types::utils::Component,
-    utils::{APPLICATION_NAME_DESKTOP, file_picker},
+    App, ApplicationModel, BackupModel, CatalogGameModel, DuplicateGroupModel, ProfileModel,
+    Singleton, TargetModel,
+    artwork::{self, Crop, POSTER_FORMATS},
+    catalog::{self, CatalogGame},
+    config::{Config, get_config, reload_config},
+    depots::Depot,
+    error::{GProfilesError, Result},
+    library::{self, InstalledGame},
+    merge::Resolution,
+    pe, posters, relocate,
+    types::{logitech::Application, utils::Component},
+    utils::{APPLICATION_NAME_DESKTOP, Cast, file_picker},
 };
 
+// Longest side of the image shown while cropping
+const CROP_PREVIEW_SIZE: u32 = 512;
+// Games of LGHUB's database listed at once, the rest is left to a more precise search
+const CATALOG_RESULTS: usize = 100;
+
+thread_local! {
+    // Image picked as a poster and the application it is for, while it is being cropped
+    static CROPPING: RefCell<Option<(String, DynamicImage)>> = const { RefCell::new(None) };
+    // LGHUB's database of games, read on the first search after each reload
+    static CATALOG: RefCell<Option<Vec<CatalogGame>>> = const { RefCell::new(None) };
+}
+
+fn cache_poster(
+    image: &DynamicImage,
+    crop: &Crop,
+    icon_cache: &Option<path::PathBuf>,
+) -> Result<Option<path::PathBuf>> {
+    let dynimg = artwork::crop(image, crop);
+    if let Some(icon_cache) = icon_cache {
+        let f = icon_cache.join(Uuid::new_v4().to_string() + ".bmp");
+        dynimg.save_with_format(&f, image::ImageFormat::Bmp)?;
+        return Ok(Some(f));
+    }
+    Ok(None)
+}
+
+// Only once LGHUB's settings no longer refer to them
+fn remove_images(images: &[path::PathBuf]) -> Result<()> {
+    for image in images {
+        fs::remove_file(image)?;
+    }
+    Ok(())
+}
+
+// Default poster of a new application; a missing icon is not worth an error
+fn executable_poster(
+    executable: &path::Path,
+    icon_cache: &Option<path::PathBuf>,
+) -> Option<String> {
+    pe::icon_from_executable(executable)
+        .and_then(|icon| cache_poster(&icon, &Crop::default(), icon_cache))
+        .ok()
+        .flatten()
+        .map(|f| f.to_string_lossy().to_string())
+}
+
 impl App {
     pub fn set_callbacks(&self) {
         let singleton = self.global::<Singleton>();
 
         let weak = self.as_weak();
+        singleton.on_create_application({
+            let weak = weak.clone();
+            move || weak.unwrap().on_create_application()
+        });
+        singleton.on_scan_library({
+            let weak = weak.clone();
+            move || weak.unwrap().on_scan_library()
+        });
+        singleton.on_toggle_library_game({
+            let weak = weak.clone();
+            move |index| weak.unwrap().on_toggle_library_game(index)
+        });
+        singleton.on_import_library({
+            let weak = weak.clone();
+            move || weak.unwrap().on_import_library()
+        });
+        singleton.on_search_catalog({
+            let weak = weak.clone();
+            move |query| weak.unwrap().on_search_catalog(query.to_string())
+        });
+        singleton.on_add_catalog_game({
+            let weak = weak.clone();
+            move |game| weak.unwrap().on_add_catalog_game(game)
+        });
+        singleton.on_add_search_root({
+            let weak = weak.clone();
+            move || weak.unwrap().on_add_search_root()
+        });
+        singleton.on_preview_relocations({
+            let weak = weak.clone();
+            move || weak.unwrap().on_preview_relocations()
+        });
+        singleton.on_apply_relocations({
+            let weak = weak.clone();
+            move || weak.unwrap().on_apply_relocations()
+        });
+        singleton.on_find_duplicates({
+            let weak = weak.clone();
+            move || weak.unwrap().on_find_duplicates()
+        });
+        singleton.on_merge_duplicates({
+            let weak = weak.clone();
+            move |group| weak.unwrap().on_merge_duplicates(group)
+        });
+        singleton.on_duplicate_application({
+            let weak = weak.clone();
+            move || weak.unwrap().on_duplicate_application()
+        });
         singleton.on_select_application({
             let weak = weak.clone();
             move |application| weak.unwrap().on_select_application(application)
@@ -28,10 +134,374 @@ impl App {
             let weak = weak.clone();
             move || weak.unwrap().on_change_image()
         });
+        singleton.on_apply_crop({
+            let weak = weak.clone();
+            move || weak.unwrap().on_apply_crop()
+        });
+        singleton.on_cancel_crop({
+            let weak = weak.clone();
+            move || weak.unwrap().on_cancel_crop()
+        });
         singleton.on_change_executable({
             let weak = weak.clone();
             move || weak.unwrap().on_change_executable()
         });
+        singleton.on_duplicate_profile({
+            let weak = weak.clone();
+            move |application_id| {
+                weak.unwrap()
+                    .on_duplicate_profile(application_id.to_string())
+            }
+        });
+        singleton.on_select_profile({
+            let weak = weak.clone();
+            move |profile| weak.unwrap().on_select_profile(profile)
+        });
+        singleton.on_name_profile({
+            let weak = weak.clone();
+            move |name| weak.unwrap().on_name_profile(name.to_string())
+        });
+        singleton.on_delete_profile({
+            let weak = weak.clone();
+            move || weak.unwrap().on_delete_profile()
+        });
+        singleton.on_delete_application({
+            let weak = weak.clone();
+            move || weak.unwrap().on_delete_application()
+        });
+        singleton.on_restore_backup({
+            let weak = weak.clone();
+            move |backup| weak.unwrap().on_restore_backup(backup)
+        });
+        singleton.on_select_settings({
+            let weak = weak.clone();
+            move || weak.unwrap().on_select_settings()
+        });
+        singleton.on_select_target({
+            let weak = weak.clone();
+            move |target| weak.unwrap().on_select_target(target)
+        });
+        singleton.on_remove_target({
+            let weak = weak.clone();
+            move |target| weak.unwrap().on_remove_target(target)
+        });
+    }
+
+    fn on_create_application(&self) {
+        let singleton = self.global::<Singleton>();
+        let Some(fp) = file_picker("Executable", &["exe"], None) else {
+            return;
+        };
+        let name = fp
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut config = get_config().write().unwrap();
+        let poster = executable_poster(&fp, &config.get_icon_cache());
+        let id = config.create_application(name, fp.to_string_lossy().to_string(), poster);
+        singleton.set_active_application_id(SharedString::from(id));
+        self.save(config);
+    }
+
+    fn on_scan_library(&self) {
+        let singleton = self.global::<Singleton>();
+        if singleton.get_scanning() {
+            return;
+        }
+        let config = get_config().read().unwrap();
+        let sources: Vec<_> = library::sources()
+            .into_iter()
+            .map(|source| {
+                let roots = config
+                    .get_library_roots(source.name())
+                    .unwrap_or_else(|| source.default_roots());
+                (source, roots)
+            })
+            .collect();
+        drop(config);
+
+        // Launchers may keep their libraries on slow or network drives
+        singleton.set_scanning(true);
+        let weak = self.as_weak();
+        thread::spawn(move || {
+            let mut games: Vec<InstalledGame> = vec![];
+            let mut failed: Option<GProfilesError> = None;
+            // One broken launcher should not hide the games of the others
+            for (source, roots) in sources {
+                match source.scan(&roots) {
+                    Ok(found) => games.extend(found),
+                    Err(e) => failed = Some(e),
+                }
+            }
+            // Event loop may be gone, nobody is waiting for the library then
+            let _ = weak.upgrade_in_event_loop(move |app| app.on_library_scanned(games, failed));
+        });
+    }
+
+    fn on_library_scanned(&self, mut games: Vec<InstalledGame>, failed: Option<GProfilesError>) {
+        let singleton = self.global::<Singleton>();
+        singleton.set_scanning(false);
+        // Games that already have an application are not worth offering again
+        let config = get_config().read().unwrap();
+        games.retain(|g| {
+            g.executable
+                .as_ref()
+                .is_none_or(|e| config.find_application_by_executable(e).is_none())
+        });
+        drop(config);
+        singleton.set_library(games.as_component());
+        if let Some(e) = failed {
+            singleton.show_error(&e);
+        }
+    }
+
+    fn on_toggle_library_game(&self, index: i32) {
+        let library = self.global::<Singleton>().get_library();
+        if let Some(mut game) = library.row_data(index as usize) {
+            game.selected = !game.selected && !game.executable.is_empty();
+            library.set_row_data(index as usize, game);
+        }
+    }
+
+    fn on_import_library(&self) {
+        let singleton = self.global::<Singleton>();
+        let library = singleton.get_library();
+        let (selected, remaining): (Vec<_>, Vec<_>) = library.iter().partition(|g| g.selected);
+        if selected.is_empty() {
+            return;
+        }
+        let mut config = get_config().write().unwrap();
+        let icon_cache = config.get_icon_cache();
+        for game in selected {
+            let artwork = path::Path::new(game.artwork.as_str());
+            // Launchers may point at an image, or at an executable carrying the icon
+            let poster = if game.artwork.is_empty() {
+                executable_poster(path::Path::new(game.executable.as_str()), &icon_cache)
+            } else if artwork
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("exe"))
+            {
+                executable_poster(artwork, &icon_cache)
+            } else {
+                artwork::open(artwork)
+                    .and_then(|image| cache_poster(&image, &Crop::default(), &icon_cache))
+                    .ok()
+                    .flatten()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .or(Some(game.artwork.to_string()))
+            };
+            let id = config.create_application(
+                game.name.to_string(),
+                game.executable.to_string(),
+                poster,
+            );
+            singleton.set_active_application_id(SharedString::from(id));
+        }
+        singleton.set_library(ModelRc::new(VecModel::from(remaining)));
+        self.save(config);
+    }
+
+    fn on_search_catalog(&self, query: String) {
+        let singleton = self.global::<Singleton>();
+        if CATALOG.with_borrow(|c| c.is_none()) {
+            let root = get_config().read().unwrap().get_depots_root();
+            match Depot::open(&root).and_then(|depot| catalog::read(&depot)) {
+                Ok(games) => CATALOG.set(Some(games)),
+                Err(e) => {
+                    singleton.show_error(&e);
+                    return;
+                }
+            }
+        }
+
+        let config = get_config().read().unwrap();
+        let items: Vec<CatalogGameModel> = CATALOG.with_borrow(|games| {
+            catalog::search(games.as_deref().unwrap_or_default(), &query)
+                .into_iter()
+                .take(CATALOG_RESULTS)
+                .map(|game| CatalogGameModel {
+                    added: config
+                        .find_application_by_database_id(&game.database_id)
+                        .is_some(),
+                    ..game.as_component()
+                })
+                .collect()
+        });
+        singleton.set_catalog(ModelRc::new(VecModel::from(items)));
+    }
+
+    fn on_add_catalog_game(&self, model: CatalogGameModel) {
+        let singleton = self.global::<Singleton>();
+        let Some(game) = CATALOG.with_borrow(|games| {
+            games
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .find(|g| g.database_id == model.id.as_str())
+                .cloned()
+        }) else {
+            return;
+        };
+
+        let mut config = get_config().write().unwrap();
+        match config.add_catalog_application(&game) {
+            Ok(id) => singleton.set_active_application_id(SharedString::from(id)),
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        }
+        let catalog = singleton.get_catalog();
+        if let Some(index) = catalog.iter().position(|g| g.id == model.id) {
+            catalog.set_row_data(
+                index,
+                CatalogGameModel {
+                    added: true,
+                    ..model
+                },
+            );
+        }
+        self.save(config);
+    }
+
+    fn on_add_search_root(&self) {
+        let singleton = self.global::<Singleton>();
+        let Some(root) = FileDialog::new().set_title("Search folder").pick_folder() else {
+            return;
+        };
+        let root = SharedString::from(root.to_string_lossy().to_string());
+        let mut roots: Vec<SharedString> = singleton.get_search_roots().iter().collect();
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+        singleton.set_search_roots(ModelRc::new(VecModel::from(roots)));
+    }
+
+    fn on_preview_relocations(&self) {
+        let singleton = self.global::<Singleton>();
+        if singleton.get_searching() {
+            return;
+        }
+        let (from, to) = (
+            singleton.get_relocate_from().to_string(),
+            singleton.get_relocate_to().to_string(),
+        );
+        let roots: Vec<path::PathBuf> = singleton
+            .get_search_roots()
+            .iter()
+            .map(|r| path::PathBuf::from(r.as_str()))
+            .collect();
+        let mut relocations = get_config().read().unwrap().find_broken_paths();
+
+        // Search roots may be whole drives
+        singleton.set_searching(true);
+        let weak = self.as_weak();
+        thread::spawn(move || {
+            let prefix = (!from.is_empty()).then_some((from.as_str(), to.as_str()));
+            relocate::suggest(&mut relocations, prefix, &roots);
+            // Event loop may be gone, nobody is waiting for the preview then
+            let _ = weak.upgrade_in_event_loop(move |app| {
+                let singleton = app.global::<Singleton>();
+                singleton.set_searching(false);
+                singleton.set_relocations(relocations.as_component());
+            });
+        });
+    }
+
+    fn on_apply_relocations(&self) {
+        let singleton = self.global::<Singleton>();
+        let previewed = singleton.get_relocations();
+        let mut config = get_config().write().unwrap();
+        // Only paths that are still missing are moved, in case they came back since the preview
+        let mut relocations = config.find_broken_paths();
+        for relocation in relocations.iter_mut() {
+            relocation.to = previewed
+                .iter()
+                .find(|r| {
+                    r.id == relocation.application_id.as_str()
+                        && r.from == relocation.from.as_str()
+                        && r.field == relocation.field.label()
+                })
+                .filter(|r| !r.to.is_empty())
+                .map(|r| r.to.to_string());
+        }
+        if config.relocate(&relocations) == 0 {
+            return;
+        }
+        singleton.set_relocations(ModelRc::default());
+        self.save(config);
+    }
+
+    fn on_find_duplicates(&self) {
+        let singleton = self.global::<Singleton>();
+        let groups = get_config().read().unwrap().get_duplicate_groups();
+        singleton.set_duplicates(groups.as_component());
+    }
+
+    fn on_merge_duplicates(&self, model: DuplicateGroupModel) {
+        let singleton = self.global::<Singleton>();
+        // The group may have changed since it was listed
+        let groups = get_config().read().unwrap().get_duplicate_groups();
+        let Some(group) = groups
+            .iter()
+            .find(|g| g.executable == model.executable.as_str())
+        else {
+            singleton.set_duplicates(groups.as_component());
+            return;
+        };
+        let active = singleton.get_active_application_id().to_string();
+        let keep = group.keep(&active);
+        let others: Vec<&Application> = group
+            .applications
+            .iter()
+            .filter(|a| a.applicationId != keep.applicationId)
+            .collect();
+        let ids: Vec<String> = others.iter().map(|a| a.applicationId.clone()).collect();
+        // Refused before asking rather than after
+        let checked = get_config()
+            .read()
+            .unwrap()
+            .check_merge(&keep.applicationId, &ids);
+        if let Err(e) = checked {
+            singleton.show_error(&e);
+            return;
+        }
+
+        let choice = MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Merge applications")
+            .set_description(format!(
+                "The profiles and keybinds of the following will be moved to {}, and they will be removed:\n\n{}",
+                keep.name,
+                others
+                    .iter()
+                    .map(|a| a.name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n")
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if choice != MessageDialogResult::Yes {
+            return;
+        }
+        // Checked again, the settings may have been reloaded while the dialog was open
+        let mut config = get_config().write().unwrap();
+        let images = match config.merge_applications(&keep.applicationId, &ids) {
+            Ok(images) => images,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        };
+        if ids.contains(&active) {
+            singleton.set_active_application_id(SharedString::from(&keep.applicationId));
+        }
+        singleton.set_duplicates(config.get_duplicate_groups().as_component());
+        if self.save(config)
+            && let Err(e) = remove_images(&images)
+        {
+            singleton.show_error(&e);
+        }
     }
 
     fn on_select_application(&self, model: ApplicationModel) {
@@ -50,65 +520,376 @@ impl App {
             a.name = name.clone();
             a
         });
-        drop(config);
-        singleton.sync_applications();
-        singleton.sync_active_application(&id);
+        self.save(config);
     }
 
     fn on_change_image(&self) {
         let singleton = self.global::<Singleton>();
         let id = singleton.get_active_application_id().to_string();
+        let dir = match get_config().read().unwrap().get_application(&id) {
+            Some(app) if app.isCustom.is_some() => app.posterPath.clone(),
+            _ => None,
+        };
+        let Some(file) =
+            dir.and_then(|d| file_picker("Image", &POSTER_FORMATS, Some(path::Path::new(&d))))
+        else {
+            return;
+        };
+        let image = match artwork::open(&file) {
+            Ok(image) => image,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        };
+
+        // Cropping happens on a copy small enough to redraw while dragging
+        let preview = image.thumbnail(CROP_PREVIEW_SIZE, CROP_PREVIEW_SIZE);
+        let crop = Crop::default();
+        singleton.set_crop_source(Image::using(preview));
+        singleton.set_crop_aspect_ratio(image.width() as f32 / image.height() as f32);
+        singleton.set_crop_x(crop.x);
+        singleton.set_crop_y(crop.y);
+        singleton.set_crop_zoom(crop.zoom);
+        singleton.set_cropping(true);
+        CROPPING.set(Some((id, image)));
+    }
+
+    fn on_apply_crop(&self) {
+        let singleton = self.global::<Singleton>();
+        singleton.set_cropping(false);
+        singleton.set_crop_source(Image::default());
+        let Some((id, image)) = CROPPING.take() else {
+            return;
+        };
+        let crop = Crop {
+            x: singleton.get_crop_x(),
+            y: singleton.get_crop_y(),
+            zoom: singleton.get_crop_zoom(),
+        };
+
         let mut config = get_config().write().unwrap();
-        let maybe_icon_cache = config.get_icon_cache();
-        config.edit_application(&id, |mut app| {
-            if app.isCustom.is_none() {
-                return app;
+        let poster = match cache_poster(&image, &crop, &config.get_icon_cache()) {
+            Ok(Some(f)) => f.to_string_lossy().to_string(),
+            Ok(None) => return,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
             }
-            if let Some(dir) = app.posterPath.clone()
-                && let Some(fp) = file_picker(
-                    "Image",
-                    &["bmp", "png", "jpg", "jpeg"],
-                    Some(path::Path::new(&dir)),
-                )
-            {
-                let dynimg = ImageReader::open(fp)
-                    .unwrap()
-                    .with_guessed_format()
-                    .unwrap()
-                    .decode()
-                    .unwrap()
-                    .resize(256, 256, CatmullRom);
-                if let Some(icon_cache) = &maybe_icon_cache {
-                    let f = icon_cache.join(Uuid::new_v4().to_string() + ".bmp");
-                    dynimg
-                        .save_with_format(&f, image::ImageFormat::Bmp)
-                        .unwrap();
-                    app.posterPath = Some(f.to_string_lossy().to_string());
-                }
+        };
+        config.edit_application(&id, |mut app| {
+            app.posterPath = Some(poster.clone());
+            if singleton.get_active_application_id() == app.applicationId.as_str() {
+                singleton.sync_application_details(&app.as_component());
             }
-            singleton.sync_application_details(&app.as_component());
             app
         });
-        drop(config);
-        singleton.sync_applications();
+        self.save(config);
+    }
+
+    fn on_cancel_crop(&self) {
+        let singleton = self.global::<Singleton>();
+        singleton.set_cropping(false);
+        singleton.set_crop_source(Image::default());
+        CROPPING.set(None);
     }
 
     fn on_change_executable(&self) {
         let singleton = self.global::<Singleton>();
         let id = singleton.get_active_application_id().to_string();
+        let dir = match get_config().read().unwrap().get_application(&id) {
+            Some(app) if app.isCustom.is_some() => app.applicationPath.clone(),
+            _ => return,
+        };
+        let Some(fp) =
+            dir.and_then(|d| file_picker("Executable", &["exe"], Some(path::Path::new(&d))))
+        else {
+            return;
+        };
         let mut config = get_config().write().unwrap();
         config.edit_application(&id, |mut app| {
-            if app.isCustom.is_none() {
-                return app;
-            }
-            if let Some(dir) = app.applicationPath.clone()
-                && let Some(fp) = file_picker("Executable", &["exe"], Some(path::Path::new(&dir)))
-            {
-                app.applicationPath = Some(fp.to_string_lossy().to_string());
-            }
+            app.applicationPath = Some(fp.to_string_lossy().to_string());
             singleton.sync_application_details(&app.as_component());
             app
         });
+        self.save(config);
+    }
+
+    fn on_duplicate_application(&self) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_active_application_id().to_string();
+        let mut config = get_config().write().unwrap();
+        match config.duplicate_application(&id) {
+            Ok(copy) => singleton.set_active_application_id(SharedString::from(copy)),
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        }
+        self.save(config);
+    }
+
+    fn on_duplicate_profile(&self, application_id: String) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_profile_id().to_string();
+        let mut config = get_config().write().unwrap();
+        let copy = match config.duplicate_profile(&id, &application_id) {
+            Ok(copy) => copy,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        };
+        let name = config
+            .get_profiles_for(&application_id)
+            .into_iter()
+            .find(|p| p.id == copy)
+            .map(|p| p.name)
+            .unwrap_or_default();
+        // Show the profiles of the application the copy went to, with the copy selected
+        singleton.set_active_application_id(SharedString::from(&application_id));
+        self.save(config);
+        singleton.set_profile_id(SharedString::from(copy));
+        singleton.set_profile_name(SharedString::from(name));
+    }
+
+    fn on_select_profile(&self, model: ProfileModel) {
+        let singleton = self.global::<Singleton>();
+        let mut config = get_config().write().unwrap();
+        if let Err(e) = config.activate_profile(&model.id.to_string()) {
+            singleton.show_error(&e);
+            return;
+        }
+        self.save(config);
+    }
+
+    fn on_name_profile(&self, name: String) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_profile_id().to_string();
+        let mut config = get_config().write().unwrap();
+        let result = config.rename_profile(&id, &name);
+        let name = config.get_profile(&id).map(|p| p.name.clone());
+        if let Err(e) = result {
+            singleton.show_error(&e);
+            singleton.set_profile_name(SharedString::from(name.unwrap_or_default()));
+            return;
+        }
+        self.save(config);
+        // The renamed profile stays selected, even if it is not the active one
+        singleton.set_profile_id(SharedString::from(id));
+        singleton.set_profile_name(SharedString::from(name.unwrap_or_default()));
+    }
+
+    fn on_delete_profile(&self) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_profile_id().to_string();
+        let choice = MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Delete profile")
+            .set_description(format!(
+                "The profile {} and its assignments will be removed.",
+                singleton.get_profile_name()
+            ))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if choice != MessageDialogResult::Yes {
+            return;
+        }
+        let mut config = get_config().write().unwrap();
+        if let Err(e) = config.delete_profile(&id) {
+            singleton.show_error(&e);
+            return;
+        }
+        self.save(config);
+    }
+
+    fn on_delete_application(&self) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_active_application_id().to_string();
+        let preview = get_config().read().unwrap().preview_deletion(&id);
+        let deletion = match preview {
+            Ok(deletion) => deletion,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        };
+        let choice = MessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Delete application")
+            .set_description(format!("The following will be removed:\n\n{}", deletion))
+            .set_buttons(MessageButtons::YesNo)
+            .show();
+        if choice != MessageDialogResult::Yes {
+            return;
+        }
+        let mut config = get_config().write().unwrap();
+        let deletion = match config.delete_application(&id) {
+            Ok(deletion) => deletion,
+            Err(e) => {
+                singleton.show_error(&e);
+                return;
+            }
+        };
+        singleton.set_active_application_id(SharedString::new());
+        if self.save(config)
+            && let Err(e) = remove_images(&deletion.images)
+        {
+            singleton.show_error(&e);
+        }
+    }
+
+    fn on_restore_backup(&self, model: BackupModel) {
+        let config = get_config().read().unwrap();
+        let restored = match config
+            .get_backups()
+            .iter()
+            .find(|b| b.path == path::Path::new(model.path.as_str()))
+        {
+            Some(backup) => config.restore_backup(backup),
+            None => return,
+        };
+        drop(config);
+        if let Err(e) = restored {
+            self.global::<Singleton>().show_error(&e);
+            return;
+        }
+        self.reload();
+    }
+
+    fn on_select_settings(&self) {
+        let current = get_config()
+            .read()
+            .unwrap()
+            .get_lghub_location()
+            .to_path_buf();
+        let Some(location) = FileDialog::new()
+            .set_title("LGHUB settings location")
+            .set_directory(current)
+            .pick_folder()
+        else {
+            return;
+        };
+        let mut config = get_config().write().unwrap();
+        let result = match config.add_target(&location) {
+            Ok(target) => config
+                .select_target(&target.name)
+                .and_then(|_| config.save_gprofiles()),
+            Err(e) => Err(e),
+        };
+        drop(config);
+        match result {
+            Ok(_) => self.reload(),
+            Err(e) => self.global::<Singleton>().show_error(&e),
+        }
+    }
+
+    fn on_select_target(&self, model: TargetModel) {
+        let mut config = get_config().write().unwrap();
+        let result = config
+            .select_target(&model.name.to_string())
+            .and_then(|_| config.save_gprofiles());
+        drop(config);
+        match result {
+            Ok(_) => self.reload(),
+            Err(e) => self.global::<Singleton>().show_error(&e),
+        }
+    }
+
+    fn on_remove_target(&self, model: TargetModel) {
+        let singleton = self.global::<Singleton>();
+        let mut config = get_config().write().unwrap();
+        config.remove_target(&model.name.to_string());
+        let result = config.save_gprofiles();
+        drop(config);
+        if let Err(e) = result {
+            singleton.show_error(&e);
+        }
+        singleton.sync_advanced();
+    }
+
+    pub fn on_poster_loaded(&self, url: String, poster: Option<SharedPixelBuffer<Rgba8Pixel>>) {
+        let singleton = self.global::<Singleton>();
+        let image_path = SharedString::from(&url);
+        let (has_icon, icon) = match posters::loaded(url, poster) {
+            Some(image) => (true, image),
+            None => (false, Image::using(path::PathBuf::new())),
+        };
+        let applications = singleton.get_applications();
+        for row in 0..applications.row_count() {
+            if let Some(mut application) = applications.row_data(row)
+                && application.image_path == image_path
+            {
+                application.has_icon = has_icon;
+                application.icon = icon.clone();
+                applications.set_row_data(row, application);
+            }
+        }
+    }
+
+    pub fn on_settings_changed(&self) {
+        if get_config().read().unwrap().is_stale() {
+            self.reload();
+        }
+    }
+
+    fn reload(&self) {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_active_application_id().to_string();
+        // Read again on the next search, the depots may have moved along with the settings
+        CATALOG.set(None);
+        if let Err(e) = reload_config() {
+            singleton.show_error(&e);
+            return;
+        }
+        posters::retry();
+        singleton.sync();
+        singleton.sync_active_application(&id);
+    }
+
+    /// Saves LGHUB's settings and GProfiles' own, returning whether LGHUB's were saved.
+    fn save(&self, mut config: RwLockWriteGuard<'_, Config>) -> bool {
+        let singleton = self.global::<Singleton>();
+        let id = singleton.get_active_application_id().to_string();
+
+        let mut result = config.save(Resolution::Report);
+        if let Err(GProfilesError::Conflict(conflicts)) = &result {
+            let conflicts = conflicts.to_string();
+            // Unlocked while the user decides, the watcher and poster worker read the config
+            drop(config);
+            let choice = MessageDialog::new()
+                .set_level(MessageLevel::Warning)
+                .set_title("Conflicting changes")
+                .set_description(format!(
+                    "{}\n\nKeep your changes? Choose \"No\" to keep LGHUB's changes instead.",
+                    conflicts
+                ))
+                .set_buttons(MessageButtons::YesNoCancel)
+                .show();
+            config = get_config().write().unwrap();
+            result = match choice {
+                MessageDialogResult::Yes => config.save(Resolution::Ours),
+                MessageDialogResult::No => config.save(Resolution::Theirs),
+                _ => result,
+            };
+        }
+        let saved_lghub = result.is_ok();
+        // Keybinds live in GProfiles' own settings, they are kept even when LGHUB's are not
+        let saved = config.save_gprofiles();
+        drop(config);
+        let errors: Vec<String> = [result.err(), saved.err()]
+            .into_iter()
+            .flatten()
+            .map(|e| e.to_string())
+            .collect();
+        if !errors.is_empty() {
+            singleton.set_error(SharedString::from(errors.join("\n")));
+        }
+
+        singleton.sync_applications();
+        singleton.sync_active_application(&id);
+        singleton.sync_advanced();
+        saved_lghub
     }
 }
 
@@ -116,6 +897,7 @@ impl Singleton<'_> {
     pub fn sync(&self) {
         self.sync_applications();
         self.sync_active_application(&String::new());
+        self.sync_advanced();
     }
 
     pub fn sync_active_application(&self, id: &String) {
@@ -188,7 +970,32 @@ impl Singleton<'_> {
         self.set_applications(ModelRc::new(VecModel::from(items)));
     }
 
-    // pub fn sync_advanced(&self) {}
+    pub fn show_error(&self, error: &GProfilesError) {
+        self.set_error(SharedString::from(error.to_string()));
+    }
+
+    pub fn sync_backups(&self) {
+        let config = get_config().read().unwrap();
+        self.set_backups(config.get_backups().as_component());
+    }
+
+    pub fn sync_advanced(&self) {
+        let config = get_config().read().unwrap();
+        let location = config.get_lghub_location().to_string_lossy().to_string();
+        let targets: Vec<TargetModel> = config
+            .get_targets()
+            .iter()
+            .map(|t| TargetModel {
+                name: SharedString::from(&t.name),
+                path: SharedString::from(&t.path),
+                active: t.path == location,
+            })
+            .collect();
+        self.set_settings_path(SharedString::from(location));
+        self.set_targets(ModelRc::new(VecModel::from(targets)));
+        drop(config);
+        self.sync_backups();
+    }
 
     // pub fn sync_processes(&self) {}
 
@@ -1,22 +1,42 @@
 use image::{DynamicImage, ImageReader};
 use rfd::FileDialog;
-use rusqlite::{Connection, types::FromSql};
-use serde::{Deserialize, Serialize};
 use slint::{Image, SharedPixelBuffer};
-use std::{io, path};
+
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io, path,
+};
+
+use crate::error::Result;
 
 pub const APPLICATION_NAME_DESKTOP: &str = "APPLICATION_NAME_DESKTOP";
 pub const PROFILE_NAME_DEFAULT: &str = "PROFILE_NAME_DEFAULT";
 pub const BROKEN_IMAGE_ICON: &[u8; 4275] = include_bytes!("../assets/broken_image.png");
 pub const DESKTOP_ICON: &[u8; 3251] = include_bytes!("../assets/desktop.png");
 
-pub fn get_row<T: FromSql>(database: &path::Path, table: &str, row: &str) -> rusqlite::Result<T> {
-    let conn = Connection::open(database)?;
-    let mut stmt = conn.prepare(format!("SELECT {} FROM {};", row, table).as_str())?;
-    let row: T = stmt.query_row([], |row| Ok(row.get(0)?))?;
-    drop(stmt);
-    conn.close().unwrap();
-    Ok(row)
+pub fn fingerprint(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// "`name` (copy)", numbered from 2 onwards while `taken` says the name is in use.
+pub fn copy_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut copy = format!("{} (copy)", name);
+    let mut count = 1;
+    while taken(&copy) {
+        count += 1;
+        copy = format!("{} (copy {})", name, count);
+    }
+    copy
+}
+
+pub fn write_atomic(target: &path::Path, data: &[u8]) -> io::Result<()> {
+    let mut temporary = target.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, data)?;
+    fs::rename(&temporary, target)
 }
 
 pub fn file_picker(name: &str, ext: &[&str], dir: Option<&path::Path>) -> Option<path::PathBuf> {
@@ -27,12 +47,12 @@ pub fn file_picker(name: &str, ext: &[&str], dir: Option<&path::Path>) -> Option
     dialog.pick_file()
 }
 
-pub trait Cast<T> {
-    fn using(value: T) -> Self;
+pub fn open_image(file: &path::Path) -> Result<DynamicImage> {
+    Ok(ImageReader::open(file)?.with_guessed_format()?.decode()?)
 }
 
-pub trait Serializable<T: Serialize + for<'de> Deserialize<'de>> {
-    fn to_json(&self) -> io::Result<T>;
+pub trait Cast<T> {
+    fn using(value: T) -> Self;
 }
 
 impl Cast<DynamicImage> for Image {
@@ -48,14 +68,9 @@ impl Cast<DynamicImage> for Image {
 
 impl Cast<&path::Path> for Image {
     fn using(value: &path::Path) -> Self {
-        if !value.exists() {
-            Image::using(image::load_from_memory(BROKEN_IMAGE_ICON).unwrap())
-        } else {
-            let reader = ImageReader::open(value)
-                .unwrap()
-                .with_guessed_format()
-                .unwrap();
-            Image::using(reader.decode().unwrap())
+        match open_image(value) {
+            Ok(dynimg) => Image::using(dynimg),
+            Err(_) => Image::using(image::load_from_memory(BROKEN_IMAGE_ICON).unwrap()), // safe unwrap
         }
     }
 }
@@ -66,21 +81,10 @@ impl Cast<path::PathBuf> for Image {
     }
 }
 
-impl<T: Serialize + for<'de> Deserialize<'de>> Serializable<T> for &path::Path {
-    fn to_json(&self) -> io::Result<T> {
-        let data = std::fs::read_to_string(self)?;
-        Ok(serde_json::from_str::<T>(&data)?)
-    }
-}
-
-impl<T: Serialize + for<'de> Deserialize<'de>> Serializable<T> for path::PathBuf {
-    fn to_json(&self) -> io::Result<T> {
-        self.as_path().to_json()
-    }
-}
-
-impl<T: Serialize + for<'de> Deserialize<'de>> Serializable<T> for String {
-    fn to_json(&self) -> io::Result<T> {
-        Ok(serde_json::from_str::<T>(&self)?)
-    }
+/// An empty directory of its own for a test.
+#[cfg(test)]
+pub fn temp_dir() -> path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("gprofiles-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
 }
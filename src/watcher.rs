@@ -0,0 +1,47 @@
+use std::{fs, thread, time::Duration, time::SystemTime};
+
+use slint::ComponentHandle;
+
+use crate::{App, config::get_config};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// LGHUB writes settings.db several times in a row, wait for it to settle
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+fn snapshot() -> Vec<Option<SystemTime>> {
+    let paths = get_config().read().unwrap().get_watched_paths();
+    paths
+        .iter()
+        .map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+pub fn watch(app: &App) {
+    let weak = app.as_weak();
+    thread::spawn(move || {
+        let mut last = snapshot();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let mut current = snapshot();
+            if current == last {
+                continue;
+            }
+            loop {
+                thread::sleep(DEBOUNCE);
+                let next = snapshot();
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+            last = current;
+            if weak
+                .upgrade_in_event_loop(|app| app.on_settings_changed())
+                .is_err()
+            {
+                // Event loop is gone, nothing left to reload
+                break;
+            }
+        }
+    });
+}